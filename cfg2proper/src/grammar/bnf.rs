@@ -0,0 +1,529 @@
+// Parses grammar from BNF/EBNF notation:
+//
+// # Comments start with '#'
+// expr -> term { "+" term }
+// term -> factor [ "*" term ]
+// factor -> "(" expr ")" | id | &
+//
+// Symbols are separated by whitespace or special characters `|{}[]();`.
+// `::=` can be used instead of `->`, a rule may span several lines and can be
+// ended with `;`. Left side of the first rule is the start symbol. Symbols
+// having rules and symbols in angle brackets (<expr>) are non-terminals, other
+// symbols are terminals. Quoted symbols are always terminals, so they may
+// contain spaces or special characters, but they can't be empty or `&`, which
+// would look like epsilon. A quote inside a name is a part of
+// it, which allows primed names like E'. `&` or an empty alternative stands for
+// epsilon. EBNF constructs are desugared into fresh non-terminals:
+// { x } into N -> x N | &, [ x ] into N -> x | & and ( x | y ) into N -> x | y.
+// Comments /* like this */ and optional groups ( x | y )? of W3C EBNF notation
+// are read as well, so grammars written with --emit w3c-ebnf can be read back
+use crate::grammar::transformations::get_fresh_out_of;
+use crate::grammar::{Grammar, Origin, Production, Symbol, SymbolsKind, EPSILON_SYMBOL};
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fmt;
+
+const COMMENT_SYMBOL: char = '#';
+const SPECIAL_SYMBOLS: [char; 9] = ['|', '{', '}', '[', ']', '(', ')', ';', COMMENT_SYMBOL];
+
+#[derive(Debug)]
+enum BnfError {
+    UnterminatedString(usize),
+    UnterminatedComment(usize),
+    EmptyTerminal(usize, usize),
+    EpsilonTerminal(usize, usize),
+    ExpectedRule(usize),
+    UnexpectedToken(String, usize),
+    UnclosedBracket(String, usize),
+    NoRules,
+    TermUsedAsNonTerm(String),
+}
+
+impl fmt::Display for BnfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BnfError::UnterminatedString(line) =>
+                write!(f, "line {}: quoted terminal is not closed", line),
+            BnfError::UnterminatedComment(line) =>
+                write!(f, "line {}: comment is not closed", line),
+            BnfError::EmptyTerminal(line, column) =>
+                write!(f, "line {}, column {}: quoted terminal is empty, epsilon is {}", line, column, EPSILON_SYMBOL),
+            BnfError::EpsilonTerminal(line, column) =>
+                write!(f, "line {}, column {}: quoted terminal {} is epsilon, write it without quotes", line, column, EPSILON_SYMBOL),
+            BnfError::ExpectedRule(line) =>
+                write!(f, "line {}: rule must start with a non-terminal followed by -> or ::=", line),
+            BnfError::UnexpectedToken(token, line) =>
+                write!(f, "line {}: unexpected {}", line, token),
+            BnfError::UnclosedBracket(bracket, line) =>
+                write!(f, "line {}: {} is not closed", line, bracket),
+            BnfError::NoRules =>
+                write!(f, "grammar doesn't contain any rules"),
+            BnfError::TermUsedAsNonTerm(s) =>
+                write!(f, "quoted terminal {} is also used as a non-terminal", s),
+        }
+    }
+}
+
+impl Error for BnfError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Quoted(String),
+    Angled(String),
+    Arrow,
+    Pipe,
+    Open(char),
+    Close(char),
+    Optional,
+    Semicolon,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Name(s) => write!(f, "{}", s),
+            Token::Quoted(s) => write!(f, "\"{}\"", s),
+            Token::Angled(s) => write!(f, "<{}>", s),
+            Token::Arrow => write!(f, "->"),
+            Token::Pipe => write!(f, "|"),
+            Token::Open(c) | Token::Close(c) => write!(f, "{}", c),
+            Token::Optional => write!(f, "?"),
+            Token::Semicolon => write!(f, ";"),
+        }
+    }
+}
+
+// Right side element of a rule before desugaring
+#[derive(Debug)]
+enum Element {
+    Name(String),
+    NonTerm(String),
+    Term(String),
+    Epsilon,
+    Repeat(Vec<Vec<Element>>),
+    Optional(Vec<Vec<Element>>),
+    Group(Vec<Vec<Element>>),
+}
+
+struct Rule {
+    lhs: String,
+    alternatives: Vec<Vec<Element>>,
+    line: usize,
+}
+
+pub fn parse(content: &str) -> Result<Grammar, Box<dyn Error>> {
+    let tokens = tokenize(content)?;
+
+    let mut reader = Reader { tokens, pos: 0 };
+    let mut rules: Vec<Rule> = vec![];
+
+    while !reader.is_end() {
+        rules.push(reader.read_rule()?);
+    }
+
+    if rules.is_empty() {
+        return Err(BnfError::NoRules.into());
+    }
+
+    let mut non_terms: BTreeSet<String> = rules.iter().map(|rule| rule.lhs.clone()).collect();
+    let mut names: BTreeSet<String> = BTreeSet::new();
+    let mut quoted: BTreeSet<String> = BTreeSet::new();
+
+    for rule in rules.iter() {
+        for alternative in rule.alternatives.iter() {
+            collect_symbols(alternative, &mut non_terms, &mut names, &mut quoted);
+        }
+    }
+
+    if let Some(term) = quoted.iter().find(|v| non_terms.contains(*v)) {
+        return Err(BnfError::TermUsedAsNonTerm(term.clone()).into());
+    }
+
+    let terms: BTreeSet<String> = names
+        .into_iter()
+        .filter(|v| !non_terms.contains(v))
+        .chain(quoted)
+        .collect();
+
+    let mut desugarer = Desugarer {
+        used_names: non_terms.union(&terms).cloned().collect(),
+        non_terms,
+        productions: vec![],
+        line: 0,
+    };
+
+    for rule in rules.iter() {
+        desugarer.line = rule.line;
+        desugarer.add_rule(&rule.lhs, &rule.alternatives);
+    }
+
+    Ok(Grammar::new(desugarer.non_terms, terms, desugarer.productions, rules[0].lhs.clone()))
+}
+
+fn tokenize(content: &str) -> Result<Vec<(Token, usize)>, Box<dyn Error>> {
+    let mut tokens: Vec<(Token, usize)> = vec![];
+    // Line where the open W3C comment starts
+    let mut comment: Option<usize> = None;
+
+    for (i, line) in content.lines().enumerate() {
+        let line_n = i + 1;
+        let mut chars = line.chars().enumerate().peekable();
+        let mut after_group = false;
+
+        while let Some((column, c)) = chars.next() {
+            let is_after_group = after_group;
+            after_group = false;
+
+            if comment.is_some() {
+                if c == '*' && matches!(chars.peek(), Some((_, '/'))) {
+                    chars.next();
+                    comment = None;
+                }
+                continue;
+            }
+
+            let token = match c {
+                _ if c.is_whitespace() => continue,
+                COMMENT_SYMBOL => break,
+                '/' if matches!(chars.peek(), Some((_, '*'))) => {
+                    chars.next();
+                    comment = Some(line_n);
+                    continue;
+                }
+                // ? right after a group makes it optional, otherwise it's a name
+                '?' if is_after_group => Token::Optional,
+                '"' | '\'' => {
+                    let mut value = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, v)) if v == c => break,
+                            Some((_, v)) => value.push(v),
+                            None => return Err(BnfError::UnterminatedString(line_n).into()),
+                        }
+                    }
+
+                    // Neither of them could be told apart from epsilon in the grammar
+                    if value.is_empty() {
+                        return Err(BnfError::EmptyTerminal(line_n, column + 1).into());
+                    }
+                    if value == EPSILON_SYMBOL.to_string() {
+                        return Err(BnfError::EpsilonTerminal(line_n, column + 1).into());
+                    }
+
+                    Token::Quoted(value)
+                }
+                '|' => Token::Pipe,
+                ';' => Token::Semicolon,
+                '{' | '[' | '(' => Token::Open(c),
+                ')' => {
+                    after_group = true;
+                    Token::Close(c)
+                }
+                '}' | ']' => Token::Close(c),
+                _ => {
+                    let mut value = c.to_string();
+                    while let Some((_, v)) = chars.peek() {
+                        // Quotes open a terminal only at the start of a token, so E' is a name
+                        if v.is_whitespace() || SPECIAL_SYMBOLS.contains(v) {
+                            break;
+                        }
+                        value.push(*v);
+                        chars.next();
+                    }
+
+                    match value.as_str() {
+                        "->" | "::=" => Token::Arrow,
+                        _ if value.len() > 2 && value.starts_with('<') && value.ends_with('>') => {
+                            Token::Angled(value[1..value.len() - 1].to_string())
+                        }
+                        _ => Token::Name(value),
+                    }
+                }
+            };
+
+            tokens.push((token, line_n));
+        }
+    }
+
+    if let Some(line) = comment {
+        return Err(BnfError::UnterminatedComment(line).into());
+    }
+
+    Ok(tokens)
+}
+
+struct Reader {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Reader {
+    fn is_end(&self) -> bool {
+        self.pos == self.tokens.len()
+    }
+
+    fn peek(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + offset).map(|v| &v.0)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or_else(|| self.tokens.last())
+            .map_or(0, |v| v.1)
+    }
+
+    // Rule starts with a non-terminal followed by an arrow
+    fn is_rule_start(&self) -> bool {
+        matches!(
+            (self.peek(0), self.peek(1)),
+            (Some(Token::Name(_)), Some(Token::Arrow)) | (Some(Token::Angled(_)), Some(Token::Arrow))
+        )
+    }
+
+    fn read_rule(&mut self) -> Result<Rule, Box<dyn Error>> {
+        if !self.is_rule_start() {
+            return Err(BnfError::ExpectedRule(self.line()).into());
+        }
+
+        let lhs = match self.peek(0) {
+            Some(Token::Name(v)) | Some(Token::Angled(v)) => v.clone(),
+            _ => unreachable!(),
+        };
+        let line = self.line();
+        self.pos += 2;
+
+        let alternatives = self.read_alternatives(None)?;
+
+        if let Some(Token::Semicolon) = self.peek(0) {
+            self.pos += 1;
+        }
+
+        Ok(Rule { lhs, alternatives, line })
+    }
+
+    // Reads alternatives until the closing bracket or the end of the rule
+    fn read_alternatives(&mut self, close: Option<(char, usize)>) -> Result<Vec<Vec<Element>>, Box<dyn Error>> {
+        let mut alternatives: Vec<Vec<Element>> = vec![vec![]];
+
+        loop {
+            if close.is_none() && self.is_rule_start() {
+                break;
+            }
+
+            let token = match self.peek(0) {
+                Some(v) => v.clone(),
+                None => match close {
+                    Some((c, line)) => return Err(BnfError::UnclosedBracket(c.to_string(), line).into()),
+                    _ => break,
+                },
+            };
+
+            let line = self.line();
+            self.pos += 1;
+
+            let element = match token {
+                Token::Name(v) if v == EPSILON_SYMBOL.to_string() => Element::Epsilon,
+                Token::Name(v) => Element::Name(v),
+                Token::Angled(v) => Element::NonTerm(v),
+                Token::Quoted(v) => Element::Term(v),
+                Token::Pipe => {
+                    alternatives.push(vec![]);
+                    continue;
+                }
+                Token::Open(c) => {
+                    let inner = self.read_alternatives(Some((c, line)))?;
+                    match c {
+                        '{' => Element::Repeat(inner),
+                        '[' => Element::Optional(inner),
+                        _ if self.peek(0) == Some(&Token::Optional) => {
+                            self.pos += 1;
+                            Element::Optional(inner)
+                        }
+                        _ => Element::Group(inner),
+                    }
+                }
+                Token::Close(c) if close.map(|v| closing_bracket(v.0)) == Some(c) => break,
+                Token::Semicolon if close.is_none() => {
+                    self.pos -= 1;
+                    break;
+                }
+                token => return Err(BnfError::UnexpectedToken(token.to_string(), line).into()),
+            };
+
+            alternatives.last_mut().unwrap().push(element);
+        }
+
+        Ok(alternatives)
+    }
+}
+
+fn closing_bracket(c: char) -> char {
+    match c {
+        '{' => '}',
+        '[' => ']',
+        _ => ')',
+    }
+}
+
+// Collects non-terminals in angle brackets, bare names and quoted terminals
+fn collect_symbols(
+    elements: &[Element],
+    non_terms: &mut BTreeSet<String>,
+    names: &mut BTreeSet<String>,
+    quoted: &mut BTreeSet<String>,
+) {
+    for element in elements.iter() {
+        match element {
+            Element::Name(v) => {
+                names.insert(v.clone());
+            }
+            Element::NonTerm(v) => {
+                non_terms.insert(v.clone());
+            }
+            Element::Term(v) => {
+                quoted.insert(v.clone());
+            }
+            Element::Repeat(inner) | Element::Optional(inner) | Element::Group(inner) => {
+                for alternative in inner.iter() {
+                    collect_symbols(alternative, non_terms, names, quoted);
+                }
+            }
+            Element::Epsilon => (),
+        }
+    }
+}
+
+struct Desugarer {
+    non_terms: BTreeSet<String>,
+    used_names: BTreeSet<String>,
+    productions: Vec<Production>,
+    // Line of the rule being desugared
+    line: usize,
+}
+
+impl Desugarer {
+    fn add_rule(&mut self, lhs: &str, alternatives: &[Vec<Element>]) {
+        for alternative in alternatives.iter() {
+            let mut expression: Vec<Symbol> = alternative
+                .iter()
+                .filter_map(|element| self.lower(lhs, element))
+                .collect();
+
+            if expression.is_empty() {
+                expression.push(Symbol { kind: SymbolsKind::EPSILON, value: EPSILON_SYMBOL.to_string() });
+            }
+
+            let production = Production {
+                replaced_symbol: Symbol { kind: SymbolsKind::NONTERM, value: lhs.to_string() },
+                expression,
+                origin: self.origin(),
+            };
+            self.productions.push(production);
+        }
+    }
+
+    // Returns symbol standing for the element, EBNF constructs are replaced
+    // with fresh non-terminals. Epsilon has no symbol
+    fn lower(&mut self, lhs: &str, element: &Element) -> Option<Symbol> {
+        let (suffix, inner) = match element {
+            Element::Epsilon => return None,
+            Element::Name(v) | Element::NonTerm(v) if self.non_terms.contains(v) => {
+                return Some(Symbol { kind: SymbolsKind::NONTERM, value: v.clone() });
+            }
+            Element::Name(v) | Element::NonTerm(v) | Element::Term(v) => {
+                return Some(Symbol { kind: SymbolsKind::TERM, value: v.clone() });
+            }
+            Element::Repeat(inner) => ("_rep", inner),
+            Element::Optional(inner) => ("_opt", inner),
+            Element::Group(inner) => ("_grp", inner),
+        };
+
+        let new_non_term = get_fresh_out_of(&(lhs.to_string() + suffix), &mut self.used_names);
+        self.non_terms.insert(new_non_term.clone());
+        let symbol = Symbol { kind: SymbolsKind::NONTERM, value: new_non_term.clone() };
+
+        self.add_rule(&new_non_term, inner);
+
+        let epsilon = Symbol { kind: SymbolsKind::EPSILON, value: EPSILON_SYMBOL.to_string() };
+        match element {
+            Element::Repeat(_) => {
+                // N -> x N | &
+                for prod in self.productions.iter_mut().filter(|v| v.replaced_symbol == symbol) {
+                    prod.expression.retain(|v| v.kind != SymbolsKind::EPSILON);
+                    prod.expression.push(symbol.clone());
+                }
+                self.productions.push(Production {
+                    replaced_symbol: symbol.clone(),
+                    expression: vec![epsilon],
+                    origin: self.origin(),
+                });
+            }
+            Element::Optional(_) => {
+                self.productions.push(Production {
+                    replaced_symbol: symbol.clone(),
+                    expression: vec![epsilon],
+                    origin: self.origin(),
+                });
+            }
+            _ => (),
+        }
+
+        Some(symbol)
+    }
+
+    fn origin(&self) -> Origin {
+        Origin { line: Some(self.line), ..Origin::default() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn primes_continue_names() {
+        let g = parse("E -> T E'\nE' -> \"+\" T E' | &\nT -> 'i'").unwrap();
+
+        assert!(g.non_terms.contains("E'"));
+        assert_eq!(g.terms, ["+", "i"].iter().map(|v| v.to_string()).collect());
+
+        let prod = g.productions.iter().find(|prod| prod.replaced_symbol.value == "E'").unwrap();
+        let rhs: Vec<&str> = prod.expression.iter().map(|v| v.value.as_str()).collect();
+        assert_eq!(rhs, ["+", "T", "E'"]);
+    }
+
+    #[test]
+    fn quote_at_token_start_opens_terminal() {
+        let g = parse("S -> 'a b'\"c\" | x'").unwrap();
+
+        let rhs: Vec<Vec<&str>> = g
+            .productions
+            .iter()
+            .map(|prod| prod.expression.iter().map(|v| v.value.as_str()).collect())
+            .collect();
+        assert_eq!(rhs, [vec!["a b", "c"], vec!["x'"]]);
+    }
+
+    #[test]
+    fn empty_and_epsilon_terminals_are_rejected() {
+        let e = parse("S -> a\nA -> b \"\" c").unwrap_err();
+        assert_eq!(e.to_string(), "line 2, column 8: quoted terminal is empty, epsilon is &");
+
+        let e = parse("S -> a '&'").unwrap_err();
+        assert_eq!(e.to_string(), "line 1, column 8: quoted terminal & is epsilon, write it without quotes");
+
+        assert!(parse("S -> a &").is_ok());
+    }
+
+    #[test]
+    fn w3c_comments_and_optional_groups() {
+        let g = parse("/* a\n comment */ S ::= a ( b S )? /* tail */").unwrap();
+        let plain = parse("S -> a [ b S ]").unwrap();
+        assert_eq!(g.to_string(), plain.to_string());
+
+        let e = parse("S -> a /* open\nB -> b").unwrap_err();
+        assert_eq!(e.to_string(), "line 1: comment is not closed");
+    }
+}
@@ -0,0 +1,486 @@
+use crate::grammar::ll1::LL1Table;
+use crate::grammar::{Grammar, SymbolsKind, END_MARKER_SYMBOL, U_CODEPOINTS};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+// Names of punctuation characters used in token identifiers
+const PUNCTUATION_NAMES: &[(char, &str)] = &[
+    ('+', "Plus"),
+    ('-', "Minus"),
+    ('*', "Star"),
+    ('/', "Slash"),
+    ('%', "Percent"),
+    ('^', "Caret"),
+    ('(', "LParen"),
+    (')', "RParen"),
+    ('[', "LBracket"),
+    (']', "RBracket"),
+    ('{', "LBrace"),
+    ('}', "RBrace"),
+    ('<', "Lt"),
+    ('>', "Gt"),
+    ('=', "Eq"),
+    ('!', "Bang"),
+    ('&', "Amp"),
+    ('|', "Pipe"),
+    ('~', "Tilde"),
+    ('?', "Question"),
+    (':', "Colon"),
+    (';', "Semicolon"),
+    (',', "Comma"),
+    ('.', "Dot"),
+    ('@', "At"),
+    ('#', "Hash"),
+    ('$', "Dollar"),
+    ('"', "Quote"),
+    ('\'', "Apostrophe"),
+    ('\\', "Backslash"),
+    ('_', "Underscore"),
+];
+
+// Token standing for the end of input
+const END_TOKEN: &str = "End";
+
+// Identifiers token names can't take: the end token and Self, the only Rust
+// keyword which is CamelCase, e.g. for terminal self
+const RESERVED_TOKENS: [&str; 2] = [END_TOKEN, "Self"];
+
+/// Returns source of Rust module with predictive recursive-descent parser of
+/// LL(1) grammar: Token enum of the terminals, ParseTree type and one parsing
+/// function per non-terminal choosing production by the next token
+///
+/// # Arguments
+///
+/// * `g` - LL(1) grammar
+///
+/// * `table` - Predictive parse table of the grammar without conflicts
+pub fn generate_rust_parser(g: &Grammar, table: &LL1Table) -> String {
+    assert!(table.is_ll1, "Parser can be generated only for LL(1) grammar");
+
+    let tokens = get_token_names(&g.terms, &RESERVED_TOKENS);
+    let functions: BTreeMap<String, String> = get_snake_names(&g.non_terms, &[])
+        .into_iter()
+        .map(|(non_term, name)| (non_term, format!("parse_{}", name)))
+        .collect();
+
+    let mut out = String::new();
+    write_header(&mut out, g);
+    write_tokens(&mut out, &tokens);
+    write_runtime(&mut out, &functions[&g.start]);
+
+    for non_term in get_non_term_order(g).iter() {
+        write_function(&mut out, g, table, non_term, &tokens, &functions);
+    }
+
+    out.push_str("}\n");
+
+    out
+}
+
+fn write_header(out: &mut String, g: &Grammar) {
+    writeln!(out, "// Generated by cfg2proper, do not edit").unwrap();
+    writeln!(out, "//").unwrap();
+    writeln!(out, "// Grammar:").unwrap();
+    for prod in g.productions.iter() {
+        writeln!(out, "// {}", prod).unwrap();
+    }
+    writeln!(out, "// Start: {}", g.start).unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "use std::error::Error;").unwrap();
+    writeln!(out, "use std::fmt;").unwrap();
+    writeln!(out).unwrap();
+}
+
+fn write_tokens(out: &mut String, tokens: &BTreeMap<String, String>) {
+    writeln!(out, "/// Terminals of the grammar, End stands for the end of input").unwrap();
+    writeln!(out, "#[derive(Debug, Clone, Copy, PartialEq, Eq)]").unwrap();
+    writeln!(out, "pub enum Token {{").unwrap();
+    for name in tokens.values() {
+        writeln!(out, "    {},", name).unwrap();
+    }
+    writeln!(out, "    {},", END_TOKEN).unwrap();
+    writeln!(out, "}}").unwrap();
+    writeln!(out).unwrap();
+
+    writeln!(out, "impl Token {{").unwrap();
+    writeln!(out, "    /// Returns the token written as the text, None if it's not a terminal").unwrap();
+    writeln!(out, "    pub fn from_text(s: &str) -> Option<Token> {{").unwrap();
+    writeln!(out, "        match s {{").unwrap();
+    for (term, name) in tokens.iter() {
+        writeln!(out, "            {:?} => Some(Token::{}),", term, name).unwrap();
+    }
+    writeln!(out, "            _ => None,").unwrap();
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "    /// Returns text of the terminal").unwrap();
+    writeln!(out, "    pub fn text(&self) -> &'static str {{").unwrap();
+    writeln!(out, "        match self {{").unwrap();
+    for (term, name) in tokens.iter() {
+        writeln!(out, "            Token::{} => {:?},", name, term).unwrap();
+    }
+    writeln!(out, "            Token::{} => {:?},", END_TOKEN, END_MARKER_SYMBOL.to_string()).unwrap();
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "}}").unwrap();
+    writeln!(out).unwrap();
+}
+
+// Token-independent part: parse tree, error, tokenizer and parser state
+fn write_runtime(out: &mut String, start_function: &str) {
+    out.push_str(RUNTIME);
+    writeln!(out).unwrap();
+    writeln!(out, "/// Returns parse tree of the tokens").unwrap();
+    writeln!(out, "pub fn parse(tokens: &[Token]) -> Result<ParseTree, ParseError> {{").unwrap();
+    writeln!(out, "    let mut parser = Parser {{ tokens, pos: 0 }};").unwrap();
+    writeln!(out, "    let tree = parser.{}()?;", start_function).unwrap();
+    writeln!(out, "    parser.expect(Token::{})?;", END_TOKEN).unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "    Ok(tree)").unwrap();
+    writeln!(out, "}}").unwrap();
+    writeln!(out).unwrap();
+    out.push_str(PARSER_HELPERS);
+}
+
+const RUNTIME: &str = r#"/// Parse tree node: non-terminal with its children or terminal.
+/// Non-terminal derived by epsilon has no children
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseTree {
+    Node(&'static str, Vec<ParseTree>),
+    Leaf(Token),
+}
+
+impl ParseTree {
+    /// Returns terminals covered by the tree from left to right
+    pub fn leaves(&self) -> Vec<Token> {
+        match self {
+            ParseTree::Node(_, children) => children.iter().flat_map(|child| child.leaves()).collect(),
+            ParseTree::Leaf(token) => vec![*token],
+        }
+    }
+
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        match self {
+            ParseTree::Node(name, children) => {
+                writeln!(f, "{}{}", "  ".repeat(depth), name)?;
+                for child in children.iter() {
+                    child.fmt_indented(f, depth + 1)?;
+                }
+
+                Ok(())
+            }
+            ParseTree::Leaf(token) => writeln!(f, "{}{}", "  ".repeat(depth), token.text()),
+        }
+    }
+}
+
+impl fmt::Display for ParseTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+/// Token which can't continue the sentence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub position: usize,
+    pub found: Token,
+    pub expected: Vec<Token>,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let expected: Vec<&str> = self.expected.iter().map(|v| v.text()).collect();
+        write!(
+            f,
+            "syntax error at token {} ({}), expected one of: {}",
+            self.position + 1,
+            self.found.text(),
+            expected.join(" ")
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+/// Returns tokens of whitespace separated terminals
+pub fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    input
+        .split_whitespace()
+        .map(|s| Token::from_text(s).ok_or_else(|| format!("{} is not a terminal of the grammar", s)))
+        .collect()
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+"#;
+
+const PARSER_HELPERS: &str = r#"impl Parser<'_> {
+    fn peek(&self) -> Token {
+        self.tokens.get(self.pos).copied().unwrap_or(Token::End)
+    }
+
+    fn error(&self, expected: &[Token]) -> ParseError {
+        ParseError {
+            position: self.pos,
+            found: self.peek(),
+            expected: expected.to_vec(),
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> Result<ParseTree, ParseError> {
+        if self.peek() != token {
+            return Err(self.error(&[token]));
+        }
+
+        self.pos += 1;
+
+        Ok(ParseTree::Leaf(token))
+    }
+"#;
+
+// Function parsing the non-terminal: match over the next token selecting
+// production by the predictive table
+fn write_function(
+    out: &mut String,
+    g: &Grammar,
+    table: &LL1Table,
+    non_term: &str,
+    tokens: &BTreeMap<String, String>,
+    functions: &BTreeMap<String, String>,
+) {
+    let token = |term: &String| match tokens.get(term) {
+        Some(name) => format!("Token::{}", name),
+        None => format!("Token::{}", END_TOKEN),
+    };
+
+    let prods: Vec<_> = g.productions.iter().filter(|prod| prod.replaced_symbol.value == non_term).collect();
+
+    writeln!(out).unwrap();
+    for prod in prods.iter() {
+        writeln!(out, "    // {}", prod).unwrap();
+    }
+    writeln!(out, "    fn {}(&mut self) -> Result<ParseTree, ParseError> {{", functions[non_term]).unwrap();
+    writeln!(out, "        let children = match self.peek() {{").unwrap();
+
+    let mut expected: Vec<String> = vec![];
+
+    for prod in prods.iter() {
+        let lookaheads: Vec<String> = table
+            .terms
+            .iter()
+            .filter(|term| matches!(table.get(non_term, term), Some(v) if v.contains(prod)))
+            .map(token)
+            .collect();
+
+        if lookaheads.is_empty() {
+            continue;
+        }
+
+        let children: Vec<String> = prod
+            .expression
+            .iter()
+            .filter(|v| v.kind != SymbolsKind::EPSILON)
+            .map(|v| match v.kind {
+                SymbolsKind::NONTERM => format!("self.{}()?", functions[&v.value]),
+                _ => format!("self.expect(Token::{})?", tokens[&v.value]),
+            })
+            .collect();
+
+        writeln!(out, "            {} => vec![{}],", lookaheads.join(" | "), children.join(", ")).unwrap();
+
+        expected.extend(lookaheads);
+    }
+
+    // Every token has a production otherwise
+    if expected.len() <= tokens.len() {
+        writeln!(out, "            _ => return Err(self.error(&[{}])),", expected.join(", ")).unwrap();
+    }
+    writeln!(out, "        }};").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "        Ok(ParseTree::Node({:?}, children))", non_term).unwrap();
+    writeln!(out, "    }}").unwrap();
+}
+
+// Start symbol goes first, others in order of their first appearance as a left side
+fn get_non_term_order(g: &Grammar) -> Vec<String> {
+    let mut order: Vec<String> = vec![g.start.clone()];
+    for non_term in g.productions.iter().map(|prod| &prod.replaced_symbol.value).chain(g.non_terms.iter()) {
+        if !order.contains(non_term) {
+            order.push(non_term.clone());
+        }
+    }
+
+    order
+}
+
+/// Returns unique CamelCase identifiers of the terminals: words are capitalized
+/// and punctuation is spelled out, e.g. id -> Id, := -> ColonEq
+///
+/// # Arguments
+///
+/// * `terms` - Terminals to name
+///
+/// * `reserved` - Identifiers which can't be used
+pub fn get_token_names(terms: &BTreeSet<String>, reserved: &[&str]) -> BTreeMap<String, String> {
+    let mut used: BTreeSet<String> = reserved.iter().map(|v| v.to_string()).collect();
+
+    terms
+        .iter()
+        .map(|term| {
+            let mut name = String::new();
+            let mut word_start = true;
+
+            for c in term.chars() {
+                if c.is_ascii_alphanumeric() {
+                    match word_start {
+                        true => name.push(c.to_ascii_uppercase()),
+                        _ => name.push(c),
+                    }
+                    word_start = false;
+                    continue;
+                }
+
+                if c.is_whitespace() {
+                    word_start = true;
+                    continue;
+                }
+
+                match PUNCTUATION_NAMES.iter().find(|v| v.0 == c) {
+                    Some((_, punctuation)) => name.push_str(punctuation),
+                    None => name.push_str(&format!("U{:X}", c as u32)),
+                }
+                word_start = true;
+            }
+
+            if name.starts_with(|c: char| c.is_ascii_digit()) {
+                name.insert(0, 'T');
+            }
+
+            (term.clone(), unique_name(name, &mut used))
+        })
+        .collect()
+}
+
+/// Returns unique snake_case identifiers of the non-terminals, marks of
+/// non-terminals introduced by transformations are spelled as _quote and
+/// apostrophes as _prime, e.g. MathExpr -> math_expr, E' -> e_prime
+///
+/// # Arguments
+///
+/// * `non_terms` - Non-terminals to name
+///
+/// * `reserved` - Identifiers which can't be used
+pub fn get_snake_names(non_terms: &BTreeSet<String>, reserved: &[&str]) -> BTreeMap<String, String> {
+    let mut used: BTreeSet<String> = reserved.iter().map(|v| v.to_string()).collect();
+
+    non_terms
+        .iter()
+        .map(|non_term| {
+            let mut name = String::new();
+
+            for c in non_term.chars() {
+                match c {
+                    c if c.is_ascii_uppercase() => {
+                        name.push('_');
+                        name.push(c.to_ascii_lowercase());
+                    }
+                    c if c.is_ascii_alphanumeric() => name.push(c),
+                    c if U_CODEPOINTS.contains(&c) => name.push_str("_quote"),
+                    '\'' => name.push_str("_prime"),
+                    '_' | '-' => name.push('_'),
+                    c => name.push_str(&format!("_u{:x}", c as u32)),
+                }
+            }
+
+            while name.contains("__") {
+                name = name.replace("__", "_");
+            }
+            let mut name = name.trim_matches('_').to_string();
+            if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+                name.insert(0, 'n');
+            }
+
+            (non_term.clone(), unique_name(name, &mut used))
+        })
+        .collect()
+}
+
+/// Returns the name with the smallest numeric suffix not in `used` and
+/// adds it there
+pub fn unique_name(name: String, used: &mut BTreeSet<String>) -> String {
+    let mut unique = name.clone();
+    let mut i = 1;
+
+    while used.contains(&unique) {
+        unique = format!("{}{}", name, i);
+        i += 1;
+    }
+
+    used.insert(unique.clone());
+
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar::bnf;
+    use crate::grammar::transformations::{eliminate_indirect_lr, left_factor};
+    use crate::grammar::sets::FirstFollow;
+    use std::fs;
+    use std::process::Command;
+
+    const DRIVER: &str = r#"mod parser;
+
+fn main() {
+    let sentences = ["i + self", "( i + self ) + i", "i +", "+ i", "i i"];
+
+    for sentence in sentences.iter() {
+        let tokens = parser::tokenize(sentence).unwrap();
+        match parser::parse(&tokens) {
+            Ok(tree) => {
+                let leaves: Vec<&str> = tree.leaves().iter().map(|v| v.text()).collect();
+                println!("accepted {}", leaves.join(" "));
+            }
+            Err(e) => println!("rejected {}", e),
+        }
+    }
+}
+"#;
+
+    #[test]
+    fn generated_parser_compiles_and_parses() {
+        let g = bnf::parse("E -> E \"+\" T | T\nT -> \"(\" E \")\" | i | self").unwrap();
+        let g = left_factor(&eliminate_indirect_lr(&g, &[]));
+        let table = LL1Table::new(&g, &FirstFollow::new(&g));
+
+        let dir = std::env::temp_dir().join(format!("cfg2proper_codegen_{}", std::process::id()));
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"generated\"\nversion = \"0.1.0\"\nedition = \"2018\"\n\n[workspace]\n",
+        )
+        .unwrap();
+        fs::write(dir.join("src").join("parser.rs"), generate_rust_parser(&g, &table)).unwrap();
+        fs::write(dir.join("src").join("main.rs"), DRIVER).unwrap();
+
+        let output = Command::new(std::env::var("CARGO").unwrap_or_else(|_| String::from("cargo")))
+            .args(["run", "--quiet", "--offline"])
+            .current_dir(&dir)
+            .output()
+            .unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert_eq!(lines[0], "accepted i + self");
+        assert_eq!(lines[1], "accepted ( i + self ) + i");
+        assert!(lines[2..].iter().all(|v| v.starts_with("rejected syntax error")), "{}", stdout);
+        assert_eq!(lines.len(), 5);
+    }
+}
@@ -0,0 +1,642 @@
+use crate::grammar::parse_tree::ParseTree;
+use crate::grammar::transformations::{get_nullable_non_terms, to_cnf_traced, CnfTrace};
+use crate::grammar::{Grammar, Symbol, SymbolsKind};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+// Way a non-terminal derives a substring: either A -> a or A -> B C
+// with B deriving the first `usize` tokens of the substring
+#[derive(Debug, Clone)]
+enum Derivation {
+    Term,
+    Split(usize, String, String),
+}
+
+type Cell = BTreeMap<String, Vec<Derivation>>;
+
+// Number of trees, None if there are infinitely many of them
+type Count = Option<u128>;
+
+/// CYK parser of a grammar. Chomsky normal form of the grammar is built once
+/// and reused for every sentence, along with the number of derivations of the
+/// grammar behind every its production
+#[derive(Debug)]
+pub struct Cyk<'a> {
+    g: &'a Grammar,
+    trace: CnfTrace,
+    nullable: BTreeSet<String>,
+    cnf_start: String,
+    derives_epsilon: bool,
+    term_rules: HashMap<String, Vec<String>>,
+    pair_rules: HashMap<(String, String), Vec<String>>,
+    // Right sides of the grammar behind productions of the grammar's
+    // non-terminals and of the new start symbol in Chomsky normal form
+    right_sides: HashMap<(String, Vec<String>), Vec<String>>,
+    // Number of derivations of the grammar every such production stands for
+    weights: HashMap<(String, Vec<String>), Count>,
+}
+
+/// CYK recognition table of a sentence
+#[derive(Debug)]
+pub struct CykTable<'a> {
+    pub tokens: Vec<String>,
+    // cells[i][len - 1] contains non-terminals deriving tokens[i..i + len]
+    cells: Vec<Vec<Cell>>,
+    cyk: &'a Cyk<'a>,
+}
+
+impl<'a> Cyk<'a> {
+    /// Returns CYK parser of the grammar
+    ///
+    /// # Arguments
+    ///
+    /// * `g` - Grammar to check membership in, it's converted to Chomsky normal form
+    pub fn new(g: &'a Grammar) -> Cyk<'a> {
+        let (cnf, trace) = to_cnf_traced(g);
+
+        let derives_epsilon = cnf.productions.iter().any(|prod| {
+            prod.replaced_symbol.value == cnf.start && prod.expression.iter().all(|v| v.kind == SymbolsKind::EPSILON)
+        });
+
+        let mut term_rules: HashMap<String, Vec<String>> = HashMap::new();
+        let mut pair_rules: HashMap<(String, String), Vec<String>> = HashMap::new();
+        // Productions of non-terminals introduced by conversion, terminal proxies and
+        // parts of long right sides, each of them has exactly one production
+        let mut introduced: HashMap<&str, Vec<&Symbol>> = HashMap::new();
+
+        for prod in cnf.productions.iter() {
+            let lhs = prod.replaced_symbol.value.clone();
+            let rhs: Vec<String> = prod.expression.iter().map(|v| v.value.clone()).collect();
+
+            match prod.expression.as_slice() {
+                [a] if a.kind == SymbolsKind::TERM => term_rules.entry(rhs[0].clone()).or_default().push(lhs),
+                [_, _] => pair_rules.entry((rhs[0].clone(), rhs[1].clone())).or_default().push(lhs),
+                _ => (),
+            }
+
+            if !g.non_terms.contains(&prod.replaced_symbol.value) && prod.replaced_symbol.value != cnf.start {
+                introduced.insert(&prod.replaced_symbol.value, prod.expression.iter().collect());
+            }
+        }
+
+        // Undoes terminal proxies and splitting of long right sides
+        fn undo<'b>(symbol: &'b Symbol, introduced: &HashMap<&str, Vec<&'b Symbol>>) -> Vec<String> {
+            match introduced.get(symbol.value.as_str()) {
+                Some(rhs) if symbol.kind == SymbolsKind::NONTERM => {
+                    rhs.iter().flat_map(|v| undo(v, introduced)).collect()
+                }
+                _ => vec![symbol.value.clone()],
+            }
+        }
+
+        let mut cyk = Cyk {
+            g,
+            trace,
+            nullable: get_nullable_non_terms(g),
+            cnf_start: cnf.start.clone(),
+            derives_epsilon,
+            term_rules,
+            pair_rules,
+            right_sides: HashMap::new(),
+            weights: HashMap::new(),
+        };
+
+        let mut counter = Counter::default();
+
+        for prod in cnf.productions.iter() {
+            let lhs = &prod.replaced_symbol.value;
+            if introduced.contains_key(lhs.as_str()) || prod.expression[0].kind == SymbolsKind::EPSILON {
+                continue;
+            }
+
+            let key = (lhs.clone(), prod.expression.iter().map(|v| v.value.clone()).collect());
+            let rhs: Vec<String> = prod.expression.iter().flat_map(|v| undo(v, &introduced)).collect();
+
+            let weight = counter.derivations(&cyk, cyk.source_of(lhs), &rhs);
+            cyk.weights.insert(key.clone(), weight);
+            cyk.right_sides.insert(key, rhs);
+        }
+
+        cyk
+    }
+
+    // Non-terminal of the grammar a non-terminal of Chomsky normal form stands for,
+    // start symbols introduced by conversion stand for the start of the grammar
+    fn source_of<'b>(&'b self, non_term: &'b str) -> &'b str {
+        match non_term == self.cnf_start {
+            true => &self.g.start,
+            _ => non_term,
+        }
+    }
+
+    fn is_nullable_body(&self, body: &[Symbol]) -> bool {
+        body.iter().all(|v| v.kind == SymbolsKind::EPSILON || self.nullable.contains(&v.value))
+    }
+
+    // Returns trees of the grammar where `non_term` derives the right side `rhs`
+    // of epsilon-free grammar, children of its symbols are given as alternatives.
+    // Unit productions are followed only if they don't repeat a non-terminal of `path`
+    fn derive(
+        &self,
+        non_term: &str,
+        rhs: &[String],
+        children: &[Vec<ParseTree>],
+        path: &mut Vec<String>,
+        limit: usize,
+    ) -> Vec<ParseTree> {
+        let mut result: Vec<ParseTree> = vec![];
+
+        let key = (non_term.to_string(), rhs.to_vec());
+        for (body, erased) in self.trace.erasures.get(&key).into_iter().flatten() {
+            result.extend(self.expand(non_term, body, erased, children.to_vec(), limit - result.len()));
+            if result.len() == limit {
+                return result;
+            }
+        }
+
+        for next in self.trace.units.get(non_term).into_iter().flatten() {
+            if path.contains(next) {
+                continue;
+            }
+
+            path.push(next.clone());
+            let subtrees = self.derive(next, rhs, children, path, limit);
+            path.pop();
+
+            if subtrees.is_empty() {
+                continue;
+            }
+
+            let key = (non_term.to_string(), vec![next.clone()]);
+            for (body, erased) in self.trace.erasures.get(&key).into_iter().flatten() {
+                result.extend(self.expand(non_term, body, erased, vec![subtrees.clone()], limit - result.len()));
+                if result.len() == limit {
+                    return result;
+                }
+            }
+        }
+
+        result
+    }
+
+    // Returns trees of production `non_term` -> `body`, where deleted nullable
+    // symbols at positions `erased` derive epsilon and the rest take `kept` children
+    fn expand(
+        &self,
+        non_term: &str,
+        body: &[Symbol],
+        erased: &[usize],
+        kept: Vec<Vec<ParseTree>>,
+        limit: usize,
+    ) -> Vec<ParseTree> {
+        let mut kept = kept.into_iter();
+
+        let alternatives: Vec<Vec<ParseTree>> = body
+            .iter()
+            .enumerate()
+            .map(|(i, symbol)| match erased.contains(&i) {
+                true => self.epsilon_trees(&symbol.value, &mut vec![symbol.value.clone()], limit),
+                _ => kept.next().expect("Every kept symbol must have a child"),
+            })
+            .collect();
+
+        product(&alternatives, limit)
+            .into_iter()
+            .map(|children| ParseTree {
+                symbol: Symbol { kind: SymbolsKind::NONTERM, value: non_term.to_string() },
+                children,
+            })
+            .collect()
+    }
+
+    // Returns trees of the grammar where `non_term` derives epsilon
+    // without repeating a non-terminal of `path` on the way
+    fn epsilon_trees(&self, non_term: &str, path: &mut Vec<String>, limit: usize) -> Vec<ParseTree> {
+        let mut result: Vec<ParseTree> = vec![];
+
+        for prod in self.g.productions.iter().filter(|prod| prod.replaced_symbol.value == non_term) {
+            if !self.is_nullable_body(&prod.expression) {
+                continue;
+            }
+
+            let mut alternatives: Vec<Vec<ParseTree>> = vec![];
+            for symbol in prod.expression.iter() {
+                let trees = match symbol.kind {
+                    SymbolsKind::EPSILON => vec![ParseTree::leaf(symbol.clone())],
+                    _ if path.contains(&symbol.value) => vec![],
+                    _ => {
+                        path.push(symbol.value.clone());
+                        let trees = self.epsilon_trees(&symbol.value, path, limit);
+                        path.pop();
+                        trees
+                    }
+                };
+                alternatives.push(trees);
+            }
+
+            for children in product(&alternatives, limit - result.len()).into_iter() {
+                result.push(ParseTree { symbol: prod.replaced_symbol.clone(), children });
+            }
+
+            if result.len() == limit {
+                break;
+            }
+        }
+
+        result
+    }
+}
+
+// Counts derivations of the grammar, memoizing results across productions
+#[derive(Default)]
+struct Counter {
+    epsilon: HashMap<String, Count>,
+    units: HashMap<(String, String), Count>,
+    path: HashSet<String>,
+}
+
+impl Counter {
+    // Number of derivations A =>* X1 ... Xn of the grammar for production
+    // A -> X1 ... Xn of the grammar without unit and epsilon productions:
+    // a chain of unit productions A => ... => B followed by a production of B
+    // with the right side X1 ... Xn after deletion of nullable symbols
+    fn derivations(&mut self, cyk: &Cyk, non_term: &str, rhs: &[String]) -> Count {
+        let mut total: Count = Some(0);
+
+        for target in cyk.g.non_terms.iter() {
+            let key = (target.clone(), rhs.to_vec());
+            if !cyk.trace.erasures.contains_key(&key) {
+                continue;
+            }
+
+            let chains = self.unit_chains(cyk, non_term, target);
+            total = add(total, mul(chains, self.erasures(cyk, &key)));
+        }
+
+        total
+    }
+
+    // Number of ways to derive the right side of the epsilon-free production
+    // from source productions, by trees of deleted nullable symbols
+    fn erasures(&mut self, cyk: &Cyk, key: &(String, Vec<String>)) -> Count {
+        let mut total: Count = Some(0);
+
+        for (body, erased) in cyk.trace.erasures.get(key).into_iter().flatten() {
+            let mut n: Count = Some(1);
+            for i in erased.iter() {
+                n = mul(n, self.epsilon(cyk, &body[*i].value));
+            }
+            total = add(total, n);
+        }
+
+        total
+    }
+
+    // Number of weighted chains of unit productions from one non-terminal
+    // to another, the empty chain included
+    fn unit_chains(&mut self, cyk: &Cyk, from: &str, to: &str) -> Count {
+        let key = (from.to_string(), to.to_string());
+        if let Some(v) = self.units.get(&key) {
+            return *v;
+        }
+
+        if !reaches(cyk, from, to) {
+            return Some(0);
+        }
+
+        if !self.path.insert(from.to_string()) {
+            return None;
+        }
+
+        let mut total: Count = Some((from == to) as u128);
+        for next in cyk.trace.units.get(from).into_iter().flatten() {
+            if !reaches(cyk, next, to) {
+                continue;
+            }
+
+            let step = self.erasures(cyk, &(from.to_string(), vec![next.clone()]));
+            total = add(total, mul(step, self.unit_chains(cyk, next, to)));
+        }
+
+        self.path.remove(from);
+        self.units.insert(key, total);
+
+        total
+    }
+
+    // Number of trees of the grammar where the non-terminal derives epsilon
+    fn epsilon(&mut self, cyk: &Cyk, non_term: &str) -> Count {
+        if let Some(v) = self.epsilon.get(non_term) {
+            return *v;
+        }
+
+        if !self.path.insert(String::from("&") + non_term) {
+            return None;
+        }
+
+        let mut total: Count = Some(0);
+
+        for prod in cyk.g.productions.iter().filter(|prod| prod.replaced_symbol.value == non_term) {
+            if !cyk.is_nullable_body(&prod.expression) {
+                continue;
+            }
+
+            let mut n: Count = Some(1);
+            for symbol in prod.expression.iter().filter(|v| v.kind == SymbolsKind::NONTERM) {
+                n = mul(n, self.epsilon(cyk, &symbol.value));
+            }
+            total = add(total, n);
+        }
+
+        self.path.remove(&(String::from("&") + non_term));
+        self.epsilon.insert(non_term.to_string(), total);
+
+        total
+    }
+}
+
+impl<'a> CykTable<'a> {
+    /// Returns CYK table of the sentence
+    ///
+    /// # Arguments
+    ///
+    /// * `cyk` - Parser of the grammar to check membership in
+    ///
+    /// * `tokens` - Terminals of the sentence
+    pub fn new(cyk: &'a Cyk<'a>, tokens: &[String]) -> CykTable<'a> {
+        let n = tokens.len();
+        let mut cells: Vec<Vec<Cell>> = (0..n).map(|i| vec![Cell::new(); n - i]).collect();
+
+        for (i, token) in tokens.iter().enumerate() {
+            for lhs in cyk.term_rules.get(token).into_iter().flatten() {
+                cells[i][0].entry(lhs.clone()).or_default().push(Derivation::Term);
+            }
+        }
+
+        for len in 2..=n {
+            for i in 0..=n - len {
+                let mut cell = Cell::new();
+
+                for k in 1..len {
+                    let left = &cells[i][k - 1];
+                    let right = &cells[i + k][len - k - 1];
+
+                    for b in left.keys() {
+                        for c in right.keys() {
+                            for lhs in cyk.pair_rules.get(&(b.clone(), c.clone())).into_iter().flatten() {
+                                cell.entry(lhs.clone())
+                                    .or_default()
+                                    .push(Derivation::Split(k, b.clone(), c.clone()));
+                            }
+                        }
+                    }
+                }
+
+                cells[i][len - 1] = cell;
+            }
+        }
+
+        CykTable {
+            tokens: tokens.to_vec(),
+            cells,
+            cyk,
+        }
+    }
+
+    /// Returns whether the sentence belongs to the language of the grammar
+    pub fn accepts(&self) -> bool {
+        match self.tokens.len() {
+            0 => self.cyk.derives_epsilon,
+            n => self.cells[0][n - 1].contains_key(&self.cyk.cnf_start),
+        }
+    }
+
+    /// Returns number of parse trees of the sentence in the grammar, None if
+    /// there are infinitely many of them. Count is saturated at u128::MAX.
+    /// Every tree of Chomsky normal form stands for the trees of the grammar
+    /// with unit chains and epsilon subtrees, removed during conversion
+    pub fn count_trees(&self) -> Option<u128> {
+        match self.tokens.len() {
+            0 if self.cyk.derives_epsilon => Counter::default().epsilon(self.cyk, &self.cyk.g.start),
+            0 => Some(0),
+            n => self.count(0, n, &self.cyk.cnf_start, &mut HashMap::new()),
+        }
+    }
+
+    /// Returns parse trees of the sentence in the grammar, at most `limit` of them
+    /// if it's given. Trees repeating a non-terminal in a unit chain or in an epsilon
+    /// subtree are skipped, so that there are finitely many of them
+    pub fn trees(&self, limit: Option<usize>) -> Vec<ParseTree> {
+        if !self.accepts() {
+            return vec![];
+        }
+
+        let limit = limit.unwrap_or(usize::MAX);
+        let start = &self.cyk.g.start;
+
+        if self.tokens.is_empty() {
+            return self.cyk.epsilon_trees(start, &mut vec![start.clone()], limit);
+        }
+
+        // Every tree of Chomsky normal form is restored to at least one tree
+        let mut result: Vec<ParseTree> = vec![];
+        for tree in self.build(0, self.tokens.len(), &self.cyk.cnf_start, limit).into_iter() {
+            result.extend(self.restore(&tree, start, limit - result.len()));
+            if result.len() == limit {
+                break;
+            }
+        }
+
+        result
+    }
+
+    fn count(&self, i: usize, len: usize, non_term: &str, memo: &mut HashMap<(usize, usize, String), Count>) -> Count {
+        let key = (i, len, non_term.to_string());
+        if let Some(v) = memo.get(&key) {
+            return *v;
+        }
+
+        let mut total: Count = Some(0);
+        for derivation in self.cells[i][len - 1].get(non_term).into_iter().flatten() {
+            let (rhs, n) = match derivation {
+                Derivation::Term => (vec![self.tokens[i].clone()], Some(1)),
+                Derivation::Split(k, b, c) => {
+                    let left = self.count(i, *k, b, memo);
+                    let right = self.count(i + k, len - k, c, memo);
+                    (vec![b.clone(), c.clone()], mul(left, right))
+                }
+            };
+
+            // Productions of introduced non-terminals stand for a single derivation
+            let weight = self.cyk.weights.get(&(non_term.to_string(), rhs)).cloned().unwrap_or(Some(1));
+            total = add(total, mul(weight, n));
+        }
+
+        memo.insert(key, total);
+
+        total
+    }
+
+    // Enumerates at most `limit` trees of Chomsky normal form deriving tokens[i..i + len]
+    fn build(&self, i: usize, len: usize, non_term: &str, limit: usize) -> Vec<ParseTree> {
+        let mut result: Vec<ParseTree> = vec![];
+        let symbol = Symbol { kind: SymbolsKind::NONTERM, value: non_term.to_string() };
+
+        for derivation in self.cells[i][len - 1].get(non_term).into_iter().flatten() {
+            match derivation {
+                Derivation::Term => {
+                    let term = Symbol { kind: SymbolsKind::TERM, value: self.tokens[i].clone() };
+                    result.push(ParseTree { symbol: symbol.clone(), children: vec![ParseTree::leaf(term)] });
+                }
+                Derivation::Split(k, b, c) => {
+                    let lefts = self.build(i, *k, b, limit);
+                    for left in lefts.iter() {
+                        let rights = self.build(i + k, len - k, c, limit - result.len());
+                        for right in rights.into_iter() {
+                            result.push(ParseTree { symbol: symbol.clone(), children: vec![left.clone(), right] });
+                            if result.len() == limit {
+                                return result;
+                            }
+                        }
+                    }
+                }
+            }
+
+            if result.len() == limit {
+                break;
+            }
+        }
+
+        result
+    }
+
+    // Maps tree of Chomsky normal form rooted in `non_term` of the grammar back
+    // to trees of the grammar: terminal proxies are replaced with their terminals,
+    // non-terminals introduced by splitting long right sides are spliced into their
+    // parents, and unit chains and epsilon subtrees removed during conversion are restored
+    fn restore(&self, tree: &ParseTree, non_term: &str, limit: usize) -> Vec<ParseTree> {
+        let key = (tree.symbol.value.clone(), tree.children.iter().map(|v| v.symbol.value.clone()).collect());
+        let rhs = &self.cyk.right_sides[&key];
+
+        let mut children: Vec<Vec<ParseTree>> = vec![];
+        self.restore_children(tree, &mut children, limit);
+
+        self.cyk.derive(non_term, rhs, &children, &mut vec![non_term.to_string()], limit)
+    }
+
+    // Collects alternatives of children of the grammar's production
+    // out of the tree of Chomsky normal form
+    fn restore_children(&self, tree: &ParseTree, children: &mut Vec<Vec<ParseTree>>, limit: usize) {
+        for child in tree.children.iter() {
+            match child.symbol.kind {
+                SymbolsKind::NONTERM if self.cyk.g.non_terms.contains(&child.symbol.value) => {
+                    children.push(self.restore(child, &child.symbol.value, limit));
+                }
+                SymbolsKind::NONTERM => self.restore_children(child, children, limit),
+                _ => children.push(vec![child.clone()]),
+            }
+        }
+    }
+}
+
+// Returns whether `to` is derivable from `from` by unit productions
+fn reaches(cyk: &Cyk, from: &str, to: &str) -> bool {
+    let mut visited: BTreeSet<&str> = BTreeSet::new();
+    let mut stack: Vec<&str> = vec![from];
+
+    while let Some(v) = stack.pop() {
+        if v == to {
+            return true;
+        }
+
+        if visited.insert(v) {
+            stack.extend(cyk.trace.units.get(v).into_iter().flatten().map(|v| v.as_str()));
+        }
+    }
+
+    false
+}
+
+fn add(a: Count, b: Count) -> Count {
+    Some(a?.saturating_add(b?))
+}
+
+// Infinitely many trees times none of them is none
+fn mul(a: Count, b: Count) -> Count {
+    match (a, b) {
+        (Some(0), _) | (_, Some(0)) => Some(0),
+        _ => Some(a?.saturating_mul(b?)),
+    }
+}
+
+// Returns sequences taking one of the alternatives at every position, at most `limit` of them
+fn product(alternatives: &[Vec<ParseTree>], limit: usize) -> Vec<Vec<ParseTree>> {
+    alternatives.iter().fold(vec![vec![]], |sequences, trees| {
+        sequences
+            .iter()
+            .flat_map(|prefix| {
+                trees.iter().map(move |tree| {
+                    let mut sequence = prefix.clone();
+                    sequence.push(tree.clone());
+                    sequence
+                })
+            })
+            .take(limit)
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar::bnf;
+    use crate::grammar::earley::Chart;
+
+    fn tokens(sentence: &str) -> Vec<String> {
+        sentence.split_whitespace().map(|v| v.to_string()).collect()
+    }
+
+    fn rendered(trees: &[ParseTree]) -> BTreeSet<String> {
+        trees.iter().map(|tree| tree.to_string()).collect()
+    }
+
+    #[test]
+    fn unit_chain_is_restored() {
+        let g = bnf::parse("E -> E \"+\" T | T\nT -> T \"*\" F | F\nF -> \"(\" E \")\" | id").unwrap();
+        let cyk = Cyk::new(&g);
+        let table = CykTable::new(&cyk, &tokens("id"));
+
+        assert_eq!(table.count_trees(), Some(1));
+        assert_eq!(table.trees(None)[0].to_string(), "E\n  T\n    F\n      id\n");
+    }
+
+    #[test]
+    fn trees_and_counts_match_earley() {
+        let grammars = [
+            ("S -> a S b | S S | &", ["", "a b", "a b a b"]),
+            ("S -> A B\nA -> a A | &\nB -> b | &", ["", "a", "a a b"]),
+            ("S -> A | a\nA -> B | b\nB -> S | c", ["a", "b", "c"]),
+            ("S -> A x B\nA -> C | &\nC -> D\nD -> y | &\nB -> x | S", ["x x", "y x x", "x y x x"]),
+        ];
+
+        for (content, sentences) in grammars.iter() {
+            let g = bnf::parse(content).unwrap();
+            let cyk = Cyk::new(&g);
+
+            for sentence in sentences.iter() {
+                let table = CykTable::new(&cyk, &tokens(sentence));
+                let sppf = Chart::new(&g, &tokens(sentence)).sppf().unwrap();
+
+                assert!(table.accepts(), "{:?} {:?}", content, sentence);
+                assert_eq!(table.count_trees(), sppf.count_trees(), "{:?} {:?}", content, sentence);
+
+                let trees = table.trees(None);
+                for tree in trees.iter() {
+                    assert_eq!(tree.yield_terms(), tokens(sentence));
+                }
+                if table.count_trees().is_some() {
+                    assert_eq!(rendered(&trees), rendered(&sppf.trees(None)), "{:?} {:?}", content, sentence);
+                }
+            }
+        }
+    }
+}
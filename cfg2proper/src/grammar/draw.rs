@@ -0,0 +1,307 @@
+use crate::grammar::lr::{Lr0Automaton, Lr1Automaton};
+use crate::grammar::parse_tree::ParseTree;
+use crate::grammar::pda::Pda;
+use crate::grammar::regular::Dfa;
+use crate::grammar::SymbolsKind;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::iter;
+
+type Nd = (usize, String, SymbolsKind);
+type Ed = (usize, usize);
+
+/// Parse tree numerated for rendering: nodes in preorder and edges between them
+#[derive(Debug)]
+pub struct ParseTreeGraph {
+    nodes: Vec<Nd>,
+    edges: Vec<Ed>,
+}
+
+impl ParseTree {
+    /// Returns the tree with nodes numbered in preorder and edges between them
+    pub fn graph(&self) -> ParseTreeGraph {
+        let mut nodes: Vec<Nd> = vec![];
+        let mut edges: Vec<Ed> = vec![];
+        let mut stack: Vec<(&ParseTree, Option<usize>)> = vec![(self, None)];
+
+        while let Some((node, parent)) = stack.pop() {
+            let idx = nodes.len();
+            nodes.push((idx, node.symbol.value.clone(), node.symbol.kind));
+
+            if let Some(parent) = parent {
+                edges.push((parent, idx));
+            }
+
+            for child in node.children.iter().rev() {
+                stack.push((child, Some(idx)));
+            }
+        }
+
+        ParseTreeGraph { nodes, edges }
+    }
+}
+
+impl<'a> dot::Labeller<'a, Nd, Ed> for ParseTreeGraph {
+    fn graph_id(&self) -> dot::Id<'a> {
+        dot::Id::new("parsetree").unwrap()
+    }
+
+    fn node_id(&self, n: &Nd) -> dot::Id<'a> {
+        dot::Id::new(format!("N{}", n.0)).unwrap()
+    }
+
+    // Html labels are used since other labels escape non-ASCII symbols
+    fn node_label(&self, n: &Nd) -> dot::LabelText<'_> {
+        dot::LabelText::HtmlStr(dot::escape_html(&n.1).into())
+    }
+
+    fn node_shape(&self, n: &Nd) -> Option<dot::LabelText<'_>> {
+        match n.2 {
+            SymbolsKind::NONTERM => None,
+            _ => Some(dot::LabelText::LabelStr("plaintext".into())),
+        }
+    }
+}
+
+impl<'a> dot::GraphWalk<'a, Nd, Ed> for ParseTreeGraph {
+    fn nodes(&self) -> dot::Nodes<'a, Nd> {
+        Cow::Owned(self.nodes.clone())
+    }
+
+    fn edges(&self) -> dot::Edges<'a, Ed> {
+        Cow::Owned(self.edges.clone())
+    }
+
+    fn source(&self, e: &Ed) -> Nd {
+        self.nodes[e.0].clone()
+    }
+
+    fn target(&self, e: &Ed) -> Nd {
+        self.nodes[e.1].clone()
+    }
+}
+
+type StateNd = usize;
+type StateEd = (usize, String, usize);
+
+impl<'a> dot::Labeller<'a, StateNd, StateEd> for Lr0Automaton {
+    fn graph_id(&self) -> dot::Id<'a> {
+        dot::Id::new("lr0").unwrap()
+    }
+
+    fn node_id(&self, n: &StateNd) -> dot::Id<'a> {
+        dot::Id::new(format!("I{}", n)).unwrap()
+    }
+
+    fn node_label(&self, n: &StateNd) -> dot::LabelText<'_> {
+        let lines: Vec<String> = iter::once(format!("I{}", n))
+            .chain(self.states[*n].iter().map(|item| self.grammar.item_to_string(item)))
+            .map(|line| dot::escape_html(&line) + "<br align=\"left\"/>")
+            .collect();
+
+        dot::LabelText::HtmlStr(lines.join("").into())
+    }
+
+    fn node_shape(&self, _n: &StateNd) -> Option<dot::LabelText<'_>> {
+        Some(dot::LabelText::LabelStr("box".into()))
+    }
+
+    fn edge_label(&self, e: &StateEd) -> dot::LabelText<'_> {
+        dot::LabelText::HtmlStr(dot::escape_html(&e.1).into())
+    }
+}
+
+impl<'a> dot::GraphWalk<'a, StateNd, StateEd> for Lr0Automaton {
+    fn nodes(&self) -> dot::Nodes<'a, StateNd> {
+        (0..self.states.len()).collect()
+    }
+
+    fn edges(&self) -> dot::Edges<'a, StateEd> {
+        self.goto
+            .iter()
+            .enumerate()
+            .flat_map(|(from, transitions)| {
+                transitions
+                    .iter()
+                    .map(move |(symbol, to)| (from, symbol.clone(), *to))
+            })
+            .collect()
+    }
+
+    fn source(&self, e: &StateEd) -> StateNd {
+        e.0
+    }
+
+    fn target(&self, e: &StateEd) -> StateNd {
+        e.2
+    }
+}
+
+impl<'a> dot::Labeller<'a, StateNd, StateEd> for Lr1Automaton {
+    fn graph_id(&self) -> dot::Id<'a> {
+        dot::Id::new("lr1").unwrap()
+    }
+
+    fn node_id(&self, n: &StateNd) -> dot::Id<'a> {
+        dot::Id::new(format!("I{}", n)).unwrap()
+    }
+
+    fn node_label(&self, n: &StateNd) -> dot::LabelText<'_> {
+        let lines: Vec<String> = iter::once(format!("I{}", n))
+            .chain(self.states[*n].iter().map(|(item, lookaheads)| self.item_to_string(item, lookaheads)))
+            .map(|line| dot::escape_html(&line) + "<br align=\"left\"/>")
+            .collect();
+
+        dot::LabelText::HtmlStr(lines.join("").into())
+    }
+
+    fn node_shape(&self, _n: &StateNd) -> Option<dot::LabelText<'_>> {
+        Some(dot::LabelText::LabelStr("box".into()))
+    }
+
+    fn edge_label(&self, e: &StateEd) -> dot::LabelText<'_> {
+        dot::LabelText::HtmlStr(dot::escape_html(&e.1).into())
+    }
+}
+
+impl<'a> dot::GraphWalk<'a, StateNd, StateEd> for Lr1Automaton {
+    fn nodes(&self) -> dot::Nodes<'a, StateNd> {
+        (0..self.states.len()).collect()
+    }
+
+    fn edges(&self) -> dot::Edges<'a, StateEd> {
+        self.goto
+            .iter()
+            .enumerate()
+            .flat_map(|(from, transitions)| {
+                transitions
+                    .iter()
+                    .map(move |(symbol, to)| (from, symbol.clone(), *to))
+            })
+            .collect()
+    }
+
+    fn source(&self, e: &StateEd) -> StateNd {
+        e.0
+    }
+
+    fn target(&self, e: &StateEd) -> StateNd {
+        e.2
+    }
+}
+
+impl<'a> dot::Labeller<'a, StateNd, StateEd> for Pda {
+    fn graph_id(&self) -> dot::Id<'a> {
+        dot::Id::new("pda").unwrap()
+    }
+
+    fn node_id(&self, n: &StateNd) -> dot::Id<'a> {
+        dot::Id::new(format!("S{}", n)).unwrap()
+    }
+
+    fn node_label(&self, n: &StateNd) -> dot::LabelText<'_> {
+        dot::LabelText::HtmlStr(dot::escape_html(&self.states[*n]).into())
+    }
+
+    fn node_shape(&self, n: &StateNd) -> Option<dot::LabelText<'_>> {
+        match self.accepting.contains(n) {
+            true => Some(dot::LabelText::LabelStr("doublecircle".into())),
+            _ => Some(dot::LabelText::LabelStr("circle".into())),
+        }
+    }
+
+    fn node_style(&self, n: &StateNd) -> dot::Style {
+        match *n == self.start_state {
+            true => dot::Style::Bold,
+            _ => dot::Style::None,
+        }
+    }
+
+    fn edge_label(&self, e: &StateEd) -> dot::LabelText<'_> {
+        dot::LabelText::HtmlStr(e.1.clone().into())
+    }
+}
+
+impl<'a> dot::GraphWalk<'a, StateNd, StateEd> for Pda {
+    fn nodes(&self) -> dot::Nodes<'a, StateNd> {
+        (0..self.states.len()).collect()
+    }
+
+    // Transitions between the same states share an edge, one line per transition
+    fn edges(&self) -> dot::Edges<'a, StateEd> {
+        let mut labels: BTreeMap<(usize, usize), Vec<String>> = BTreeMap::new();
+        for transition in self.transitions.iter() {
+            labels
+                .entry((transition.from, transition.to))
+                .or_default()
+                .push(dot::escape_html(&self.label_to_string(transition)) + "<br align=\"left\"/>");
+        }
+
+        labels
+            .into_iter()
+            .map(|((from, to), lines)| (from, lines.join(""), to))
+            .collect()
+    }
+
+    fn source(&self, e: &StateEd) -> StateNd {
+        e.0
+    }
+
+    fn target(&self, e: &StateEd) -> StateNd {
+        e.2
+    }
+}
+
+// Same graph as regex2fsm renders for its Dfa: states are numbered,
+// terminal ones are bold
+impl<'a> dot::Labeller<'a, StateNd, StateEd> for Dfa {
+    fn graph_id(&self) -> dot::Id<'a> {
+        dot::Id::new("dfa").unwrap()
+    }
+
+    fn node_id(&self, n: &StateNd) -> dot::Id<'a> {
+        dot::Id::new(format!("N{}", n)).unwrap()
+    }
+
+    fn node_label(&self, n: &StateNd) -> dot::LabelText<'_> {
+        dot::LabelText::LabelStr(n.to_string().into())
+    }
+
+    fn node_style(&self, n: &StateNd) -> dot::Style {
+        match self.is_terminal.contains(n) {
+            true => dot::Style::Bold,
+            _ => dot::Style::Solid,
+        }
+    }
+
+    fn edge_label(&self, e: &StateEd) -> dot::LabelText<'_> {
+        dot::LabelText::HtmlStr(dot::escape_html(&e.1).into())
+    }
+}
+
+impl<'a> dot::GraphWalk<'a, StateNd, StateEd> for Dfa {
+    fn nodes(&self) -> dot::Nodes<'a, StateNd> {
+        (0..self.states.len()).collect()
+    }
+
+    fn edges(&self) -> dot::Edges<'a, StateEd> {
+        self.trans
+            .iter()
+            .enumerate()
+            .flat_map(|(from, targets)| {
+                targets
+                    .iter()
+                    .enumerate()
+                    .filter_map(move |(symbol, to)| to.map(|to| (from, self.alphabet[symbol].clone(), to)))
+            })
+            .collect()
+    }
+
+    fn source(&self, e: &StateEd) -> StateNd {
+        e.0
+    }
+
+    fn target(&self, e: &StateEd) -> StateNd {
+        e.2
+    }
+}
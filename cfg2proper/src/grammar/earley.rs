@@ -0,0 +1,496 @@
+use crate::grammar::parse_tree::ParseTree;
+use crate::grammar::transformations::get_nullable_non_terms;
+use crate::grammar::{Grammar, Production, Symbol, SymbolsKind, SymbolVec, EPSILON_SYMBOL};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+
+/// Earley item: production index, position of the dot within its right side
+/// without epsilon symbols and index of the set where recognition has started
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Item {
+    pub production: usize,
+    pub dot: usize,
+    pub origin: usize,
+}
+
+/// Earley chart of a sentence
+#[derive(Debug)]
+pub struct Chart<'a> {
+    pub tokens: Vec<String>,
+    pub sets: Vec<Vec<Item>>,
+    // Right sides of the productions without epsilon symbols
+    bodies: Vec<Vec<Symbol>>,
+    g: &'a Grammar,
+}
+
+/// Label of shared packed parse forest node. Symbol nodes cover
+/// tokens[from..to], intermediate nodes stand for a recognized prefix of
+/// a production and keep the forest binarized
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SppfLabel {
+    Symbol(Symbol, usize, usize),
+    Intermediate(usize, usize, usize, usize),
+}
+
+/// Node of shared packed parse forest. Every family is one alternative
+/// derivation of the node, an empty family derives epsilon
+#[derive(Debug)]
+pub struct SppfNode {
+    pub label: SppfLabel,
+    pub families: Vec<Vec<usize>>,
+}
+
+/// Shared packed parse forest of all parse trees of a sentence.
+/// The forest is cyclic if the grammar has cyclic derivations
+#[derive(Debug)]
+pub struct Sppf {
+    pub nodes: Vec<SppfNode>,
+    pub root: usize,
+}
+
+impl<'a> Chart<'a> {
+    /// Returns Earley chart of the sentence. Nullable non-terminals are
+    /// skipped right at prediction time, so epsilon productions don't need
+    /// any special completion handling
+    ///
+    /// # Arguments
+    ///
+    /// * `g` - Grammar to check membership in
+    ///
+    /// * `tokens` - Terminals of the sentence
+    pub fn new(g: &'a Grammar, tokens: &[String]) -> Chart<'a> {
+        let nullable = get_nullable_non_terms(g);
+
+        let bodies: Vec<Vec<Symbol>> = g
+            .productions
+            .iter()
+            .map(|prod| {
+                prod.expression
+                    .iter()
+                    .filter(|v| v.kind != SymbolsKind::EPSILON)
+                    .cloned()
+                    .collect()
+            })
+            .collect();
+
+        let mut chart = Chart {
+            tokens: tokens.to_vec(),
+            sets: vec![vec![]; tokens.len() + 1],
+            bodies,
+            g,
+        };
+
+        let mut seen: Vec<HashSet<Item>> = vec![HashSet::new(); tokens.len() + 1];
+
+        for (i, prod) in g.productions.iter().enumerate() {
+            if prod.replaced_symbol.value == g.start {
+                chart.add(&mut seen, 0, Item { production: i, dot: 0, origin: 0 });
+            }
+        }
+
+        for j in 0..=tokens.len() {
+            let mut k = 0;
+
+            while k < chart.sets[j].len() {
+                let item = chart.sets[j][k];
+                k += 1;
+
+                let next = match chart.bodies[item.production].get(item.dot) {
+                    Some(v) => v.clone(),
+                    None => {
+                        // Completion: A -> alpha . with origin i advances B -> beta . A gamma of set i
+                        let lhs = &g.productions[item.production].replaced_symbol.value;
+                        let waiting: Vec<Item> = chart.sets[item.origin]
+                            .iter()
+                            .filter(|v| chart.next_non_term(v) == Some(lhs))
+                            .cloned()
+                            .collect();
+
+                        for v in waiting.into_iter() {
+                            chart.add(&mut seen, j, Item { dot: v.dot + 1, ..v });
+                        }
+                        continue;
+                    }
+                };
+
+                match next.kind {
+                    SymbolsKind::TERM => {
+                        // Scanning
+                        if tokens.get(j) == Some(&next.value) {
+                            chart.add(&mut seen, j + 1, Item { dot: item.dot + 1, ..item });
+                        }
+                    }
+                    _ => {
+                        // Prediction
+                        for (i, prod) in g.productions.iter().enumerate() {
+                            if prod.replaced_symbol.value == next.value {
+                                chart.add(&mut seen, j, Item { production: i, dot: 0, origin: j });
+                            }
+                        }
+
+                        if nullable.contains(&next.value) {
+                            chart.add(&mut seen, j, Item { dot: item.dot + 1, ..item });
+                        }
+                    }
+                }
+            }
+        }
+
+        chart
+    }
+
+    /// Returns whether the sentence belongs to the language of the grammar
+    pub fn accepts(&self) -> bool {
+        self.sets[self.tokens.len()].iter().any(|item| self.is_accepting(item))
+    }
+
+    /// Returns shared packed parse forest of the sentence if it's accepted
+    pub fn sppf(&self) -> Option<Sppf> {
+        if !self.accepts() {
+            return None;
+        }
+
+        let mut builder = SppfBuilder {
+            chart: self,
+            items: self.sets.iter().map(|v| v.iter().cloned().collect()).collect(),
+            nodes: vec![],
+            index: HashMap::new(),
+        };
+
+        let start = Symbol { kind: SymbolsKind::NONTERM, value: self.g.start.clone() };
+        let root = builder.symbol_node(&start, 0, self.tokens.len());
+
+        Some(Sppf { nodes: builder.nodes, root })
+    }
+
+    fn add(&mut self, seen: &mut [HashSet<Item>], j: usize, item: Item) {
+        if seen[j].insert(item) {
+            self.sets[j].push(item);
+        }
+    }
+
+    fn next_non_term(&self, item: &Item) -> Option<&String> {
+        self.bodies[item.production]
+            .get(item.dot)
+            .filter(|v| v.kind == SymbolsKind::NONTERM)
+            .map(|v| &v.value)
+    }
+
+    fn is_complete(&self, item: &Item) -> bool {
+        item.dot == self.bodies[item.production].len()
+    }
+
+    fn is_accepting(&self, item: &Item) -> bool {
+        item.origin == 0 && self.is_complete(item) && self.g.productions[item.production].replaced_symbol.value == self.g.start
+    }
+
+    fn production(&self, item: &Item) -> &Production {
+        &self.g.productions[item.production]
+    }
+}
+
+impl<'a> Display for Chart<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (j, set) in self.sets.iter().enumerate() {
+            match self.tokens.get(j) {
+                Some(token) => writeln!(f, "S({}), next token {}:", j, token)?,
+                None => writeln!(f, "S({}):", j)?,
+            }
+
+            for item in set.iter() {
+                let body = &self.bodies[item.production];
+                let (before, after) = body.split_at(item.dot);
+                let before = SymbolVec(&before.to_vec()).to_string();
+                let after = SymbolVec(&after.to_vec()).to_string();
+
+                let marker = if self.is_accepting(item) && j == self.tokens.len() { " *" } else { "" };
+
+                let dotted: Vec<&str> = vec![before.as_str(), "•", after.as_str()]
+                    .into_iter()
+                    .filter(|v| !v.is_empty())
+                    .collect();
+
+                writeln!(
+                    f,
+                    "  {} -> {}, {}{}",
+                    self.production(item).replaced_symbol.value,
+                    dotted.join(" "),
+                    item.origin,
+                    marker
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+struct SppfBuilder<'a, 'b> {
+    chart: &'b Chart<'a>,
+    items: Vec<HashSet<Item>>,
+    nodes: Vec<SppfNode>,
+    index: HashMap<SppfLabel, usize>,
+}
+
+impl<'a, 'b> SppfBuilder<'a, 'b> {
+    // Node of a symbol deriving tokens[from..to]
+    fn symbol_node(&mut self, symbol: &Symbol, from: usize, to: usize) -> usize {
+        let label = SppfLabel::Symbol(symbol.clone(), from, to);
+        if let Some(v) = self.index.get(&label) {
+            return *v;
+        }
+
+        let id = self.new_node(label);
+
+        if symbol.kind == SymbolsKind::NONTERM {
+            let completed: Vec<Item> = self.chart.sets[to]
+                .iter()
+                .filter(|item| {
+                    item.origin == from
+                        && self.chart.is_complete(item)
+                        && self.chart.production(item).replaced_symbol.value == symbol.value
+                })
+                .cloned()
+                .collect();
+
+            for item in completed.into_iter() {
+                let families = self.families(item.production, item.dot, from, to);
+                self.nodes[id].families.extend(families);
+            }
+        }
+
+        id
+    }
+
+    // Node of a production prefix of `dot` symbols deriving tokens[from..to]
+    fn intermediate_node(&mut self, production: usize, dot: usize, from: usize, to: usize) -> usize {
+        let label = SppfLabel::Intermediate(production, dot, from, to);
+        if let Some(v) = self.index.get(&label) {
+            return *v;
+        }
+
+        let id = self.new_node(label);
+
+        let families = self.families(production, dot, from, to);
+        self.nodes[id].families.extend(families);
+
+        id
+    }
+
+    // Splits production prefix of `dot` symbols deriving tokens[from..to]
+    // into a shorter prefix and its last symbol
+    fn families(&mut self, production: usize, dot: usize, from: usize, to: usize) -> Vec<Vec<usize>> {
+        if dot == 0 {
+            return vec![vec![]];
+        }
+
+        let last = self.chart.bodies[production][dot - 1].clone();
+
+        let splits: Vec<usize> = (from..=to)
+            .filter(|k| self.items[*k].contains(&Item { production, dot: dot - 1, origin: from }))
+            .filter(|k| match last.kind {
+                SymbolsKind::TERM => *k + 1 == to && self.chart.tokens[*k] == last.value,
+                _ => self.derives(&last.value, *k, to),
+            })
+            .collect();
+
+        let mut families: Vec<Vec<usize>> = vec![];
+
+        for k in splits.into_iter() {
+            let right = self.symbol_node(&last, k, to);
+
+            match dot {
+                1 => families.push(vec![right]),
+                _ => families.push(vec![self.intermediate_node(production, dot - 1, from, k), right]),
+            }
+        }
+
+        families
+    }
+
+    fn derives(&self, non_term: &str, from: usize, to: usize) -> bool {
+        self.chart.sets[to].iter().any(|item| {
+            item.origin == from
+                && self.chart.is_complete(item)
+                && self.chart.production(item).replaced_symbol.value == non_term
+        })
+    }
+
+    fn new_node(&mut self, label: SppfLabel) -> usize {
+        self.nodes.push(SppfNode { label: label.clone(), families: vec![] });
+        self.index.insert(label, self.nodes.len() - 1);
+
+        self.nodes.len() - 1
+    }
+}
+
+impl Sppf {
+    /// Returns number of parse trees in the forest, None if there are
+    /// infinitely many of them. Count is saturated at u128::MAX
+    pub fn count_trees(&self) -> Option<u128> {
+        self.count(self.root, &mut HashMap::new(), &mut HashSet::new())
+    }
+
+    /// Returns parse trees in the forest, at most `limit` of them if it's given.
+    /// Trees of cyclic derivations are skipped
+    pub fn trees(&self, limit: Option<usize>) -> Vec<ParseTree> {
+        let limit = limit.unwrap_or(usize::MAX);
+
+        self.expand(self.root, &mut vec![], limit)
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    fn count(&self, id: usize, memo: &mut HashMap<usize, u128>, path: &mut HashSet<usize>) -> Option<u128> {
+        if let Some(v) = memo.get(&id) {
+            return Some(*v);
+        }
+
+        if !path.insert(id) {
+            return None;
+        }
+
+        let mut total: u128 = 0;
+        for family in self.nodes[id].families.iter() {
+            let mut n: u128 = 1;
+            for child in family.iter() {
+                n = n.saturating_mul(self.count(*child, memo, path)?);
+            }
+            total = total.saturating_add(n);
+        }
+
+        // Terminal leaves have no families but derive exactly one tree
+        if self.is_term(id) {
+            total = 1;
+        }
+
+        path.remove(&id);
+        memo.insert(id, total);
+
+        Some(total)
+    }
+
+    // Returns sequences of trees the node contributes to its parent:
+    // a single tree for symbol nodes, and trees of the production prefix
+    // for intermediate nodes
+    fn expand(&self, id: usize, path: &mut Vec<usize>, limit: usize) -> Vec<Vec<ParseTree>> {
+        let node = &self.nodes[id];
+
+        let symbol = match &node.label {
+            SppfLabel::Symbol(symbol, _, _) if symbol.kind != SymbolsKind::NONTERM => {
+                return vec![vec![ParseTree::leaf(symbol.clone())]];
+            }
+            SppfLabel::Symbol(symbol, _, _) => Some(symbol.clone()),
+            SppfLabel::Intermediate(..) => None,
+        };
+
+        if path.contains(&id) {
+            return vec![];
+        }
+        path.push(id);
+
+        let mut result: Vec<Vec<ParseTree>> = vec![];
+
+        for family in node.families.iter() {
+            let mut sequences: Vec<Vec<ParseTree>> = vec![vec![]];
+
+            for child in family.iter() {
+                let child_sequences = self.expand(*child, path, limit);
+
+                sequences = sequences
+                    .iter()
+                    .flat_map(|prefix| {
+                        child_sequences.iter().map(move |v| {
+                            let mut seq = prefix.clone();
+                            seq.extend(v.iter().cloned());
+                            seq
+                        })
+                    })
+                    .take(limit)
+                    .collect();
+            }
+
+            for children in sequences.into_iter() {
+                let v = match &symbol {
+                    Some(symbol) => {
+                        let children = match children.is_empty() {
+                            true => vec![ParseTree::leaf(Symbol {
+                                kind: SymbolsKind::EPSILON,
+                                value: EPSILON_SYMBOL.to_string(),
+                            })],
+                            _ => children,
+                        };
+                        vec![ParseTree { symbol: symbol.clone(), children }]
+                    }
+                    None => children,
+                };
+                result.push(v);
+
+                if result.len() == limit {
+                    path.pop();
+                    return result;
+                }
+            }
+        }
+
+        path.pop();
+
+        result
+    }
+
+    fn is_term(&self, id: usize) -> bool {
+        match &self.nodes[id].label {
+            SppfLabel::Symbol(symbol, _, _) => symbol.kind == SymbolsKind::TERM,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar::bnf;
+
+    fn chart<'a>(g: &'a Grammar, sentence: &str) -> Chart<'a> {
+        let tokens: Vec<String> = sentence.split_whitespace().map(|v| v.to_string()).collect();
+
+        Chart::new(g, &tokens)
+    }
+
+    #[test]
+    fn nullable_symbols_are_skipped() {
+        let g = bnf::parse("S -> A B\nA -> a A | &\nB -> b | &").unwrap();
+
+        for sentence in ["", "a", "b", "a a b"].iter() {
+            let sppf = chart(&g, sentence).sppf().unwrap();
+            assert_eq!(sppf.count_trees(), Some(1), "{:?}", sentence);
+        }
+        assert!(!chart(&g, "b a").accepts());
+        assert!(!chart(&g, "b b").accepts());
+
+        let trees = chart(&g, "").sppf().unwrap().trees(None);
+        assert_eq!(trees.len(), 1);
+        assert_eq!(trees[0].to_string(), "S\n  A\n    &\n  B\n    &\n");
+    }
+
+    #[test]
+    fn sppf_counts_ambiguous_trees() {
+        let g = bnf::parse("E -> E \"+\" E | id").unwrap();
+
+        let sppf = chart(&g, "id + id + id + id").sppf().unwrap();
+        assert_eq!(sppf.count_trees(), Some(5));
+        assert_eq!(sppf.trees(None).len(), 5);
+        assert_eq!(sppf.trees(Some(2)).len(), 2);
+    }
+
+    #[test]
+    fn cyclic_derivations_have_infinitely_many_trees() {
+        let g = bnf::parse("S -> a S b | S S | &").unwrap();
+
+        let sppf = chart(&g, "a b").sppf().unwrap();
+        assert_eq!(sppf.count_trees(), None);
+        assert!(!sppf.trees(None).is_empty());
+        assert!(sppf.trees(None).iter().all(|tree| tree.yield_terms() == ["a", "b"]));
+    }
+}
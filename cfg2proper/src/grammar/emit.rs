@@ -0,0 +1,391 @@
+use crate::grammar::codegen::{get_snake_names, get_token_names, unique_name};
+use crate::grammar::transformations::get_production_order;
+use crate::grammar::{Grammar, Symbol, SymbolsKind};
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt::{Display, Write};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+// Token names taken by the end of input and the whitespace lexer rule
+const RESERVED_TOKENS: [&str; 2] = ["EOF", "WS"];
+
+// Bison symbol for error recovery, it can't name a rule
+const BISON_RESERVED_RULES: [&str; 1] = ["error"];
+
+// ANTLR4 keywords, they can't name a rule
+const ANTLR_RESERVED_RULES: [&str; 14] = [
+    "import", "fragment", "lexer", "parser", "grammar", "returns", "locals", "throws", "catch", "finally", "mode",
+    "options", "tokens", "channels",
+];
+
+/// Notation of external parser generators the grammar can be exported to
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EmitFormat {
+    Bison,
+    Antlr,
+    W3cEbnf,
+}
+
+impl FromStr for EmitFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "bison" => Ok(EmitFormat::Bison),
+            "antlr" => Ok(EmitFormat::Antlr),
+            "w3c-ebnf" => Ok(EmitFormat::W3cEbnf),
+            _ => Err(String::from("Unknown emit format ") + s + ", expected one of bison, antlr, w3c-ebnf"),
+        }
+    }
+}
+
+impl Display for EmitFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmitFormat::Bison => write!(f, "bison"),
+            EmitFormat::Antlr => write!(f, "antlr"),
+            EmitFormat::W3cEbnf => write!(f, "w3c-ebnf"),
+        }
+    }
+}
+
+impl EmitFormat {
+    /// Returns extension of files in the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            EmitFormat::Bison => "y",
+            EmitFormat::Antlr => "g4",
+            EmitFormat::W3cEbnf => "ebnf",
+        }
+    }
+}
+
+/// Writes the grammar in the format to the file. ANTLR grammar is named
+/// after the file, as ANTLR requires
+///
+/// # Arguments
+///
+/// * `g` - Grammar to write
+///
+/// * `format` - Notation to write the grammar in
+///
+/// * `filename` - Output file
+pub fn write_emitted_to_file(g: &Grammar, format: EmitFormat, filename: &str) -> Result<(), Box<dyn Error>> {
+    let content = match format {
+        EmitFormat::Bison => to_bison(g),
+        EmitFormat::Antlr => {
+            let name = Path::new(filename)
+                .file_stem()
+                .and_then(|v| v.to_str())
+                .unwrap_or("Grammar");
+            to_antlr(g, name)
+        }
+        EmitFormat::W3cEbnf => to_w3c_ebnf(g)?,
+    };
+
+    fs::write(filename, content)?;
+
+    Ok(())
+}
+
+/// Returns the grammar as bison input: every terminal is declared with
+/// %token and its text as an alias, rules have no actions
+pub fn to_bison(g: &Grammar) -> String {
+    let tokens = get_upper_token_names(g);
+    let rules = get_snake_names(&g.non_terms, &BISON_RESERVED_RULES);
+
+    let mut out = String::new();
+    writeln!(out, "/* Generated by cfg2proper */").unwrap();
+    writeln!(out).unwrap();
+    for (term, name) in tokens.iter() {
+        writeln!(out, "%token {} {}", name, quote(term, '"')).unwrap();
+    }
+    writeln!(out).unwrap();
+    writeln!(out, "%start {}", rules[&g.start]).unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "%%").unwrap();
+
+    for (non_term, alternatives) in group_alternatives(g).iter() {
+        writeln!(out).unwrap();
+        writeln!(out, "{}", rules[*non_term]).unwrap();
+
+        for (i, alternative) in alternatives.iter().enumerate() {
+            let separator = if i == 0 { ':' } else { '|' };
+            let symbols = symbol_names(alternative, &tokens, &rules);
+            match symbols.is_empty() {
+                true => writeln!(out, "    {} %empty", separator).unwrap(),
+                _ => writeln!(out, "    {} {}", separator, symbols.join(" ")).unwrap(),
+            }
+        }
+
+        writeln!(out, "    ;").unwrap();
+    }
+
+    writeln!(out).unwrap();
+    writeln!(out, "%%").unwrap();
+
+    out
+}
+
+/// Returns the grammar as ANTLR4 combined grammar with a lexer rule stub
+/// matching literal text of every terminal. Start symbol rule goes first
+///
+/// # Arguments
+///
+/// * `g` - Grammar to convert
+///
+/// * `name` - Grammar name, must match the file name
+pub fn to_antlr(g: &Grammar, name: &str) -> String {
+    let tokens = get_upper_token_names(g);
+    let rules = get_snake_names(&g.non_terms, &ANTLR_RESERVED_RULES);
+
+    let mut out = String::new();
+    writeln!(out, "// Generated by cfg2proper").unwrap();
+    writeln!(out, "grammar {};", name).unwrap();
+
+    for (non_term, alternatives) in group_alternatives(g).iter() {
+        writeln!(out).unwrap();
+        writeln!(out, "{}", rules[*non_term]).unwrap();
+
+        for (i, alternative) in alternatives.iter().enumerate() {
+            let separator = if i == 0 { ':' } else { '|' };
+            let symbols = symbol_names(alternative, &tokens, &rules);
+            // Empty alternative matches epsilon
+            let line = format!("    {} {}", separator, symbols.join(" "));
+            writeln!(out, "{}", line.trim_end()).unwrap();
+        }
+
+        writeln!(out, "    ;").unwrap();
+    }
+
+    writeln!(out).unwrap();
+    writeln!(out, "// Lexer rule stubs, literals can be replaced with token patterns").unwrap();
+    for (term, name) in tokens.iter() {
+        writeln!(out, "{} : {} ;", name, quote(term, '\'')).unwrap();
+    }
+    writeln!(out).unwrap();
+    writeln!(out, "WS : [ \\t\\r\\n]+ -> skip ;").unwrap();
+
+    out
+}
+
+/// Returns the grammar in EBNF notation of W3C XML specification.
+/// Non-terminal with epsilon production is written as optional
+/// group of its other alternatives. Fails for grammars the notation can't
+/// express: empty terminals, terminals with both kinds of quotes and
+/// non-terminals deriving only epsilon
+pub fn to_w3c_ebnf(g: &Grammar) -> Result<String, Box<dyn Error>> {
+    // Plain names are kept, so renamed ones can't take them
+    let (plain, renamed): (BTreeSet<String>, BTreeSet<String>) =
+        g.non_terms.iter().cloned().partition(|v| is_plain_name(v));
+    let reserved: Vec<&str> = plain.iter().map(|v| v.as_str()).collect();
+
+    let mut names = get_snake_names(&renamed, &reserved);
+    names.extend(plain.iter().map(|v| (v.clone(), v.clone())));
+    let width = names.values().map(|v| v.chars().count()).max().unwrap_or(0);
+
+    let mut out = String::new();
+    writeln!(out, "/* Generated by cfg2proper */").unwrap();
+    writeln!(out).unwrap();
+
+    for (non_term, alternatives) in group_alternatives(g).iter() {
+        let (empty, alternatives): (Vec<&Vec<Symbol>>, Vec<&Vec<Symbol>>) = alternatives
+            .iter()
+            .partition(|v| v.iter().all(|symbol| symbol.kind == SymbolsKind::EPSILON));
+
+        let mut written: Vec<String> = vec![];
+        for alternative in alternatives.into_iter() {
+            let symbols = alternative
+                .iter()
+                .filter(|v| v.kind != SymbolsKind::EPSILON)
+                .map(|v| match v.kind {
+                    SymbolsKind::NONTERM => Ok(names[&v.value].clone()),
+                    _ => quote_w3c(&v.value),
+                })
+                .collect::<Result<Vec<String>, Box<dyn Error>>>()?;
+            written.push(symbols.join(" "));
+        }
+
+        let expression = match (empty.is_empty(), written.len()) {
+            (true, _) => written.join(" | "),
+            (false, 0) => return Err(format!("{} derives only epsilon, which W3C EBNF can't express", non_term).into()),
+            (false, _) => format!("( {} )?", written.join(" | ")),
+        };
+
+        writeln!(out, "{:<width$} ::= {}", names[*non_term], expression, width = width).unwrap();
+    }
+
+    Ok(out)
+}
+
+// UPPER_SNAKE_CASE token names as used by bison and ANTLR, e.g. NUM_LITERAL.
+// Names are made unique after the conversion, since e.g. eof and EOF differ
+// only in case
+fn get_upper_token_names(g: &Grammar) -> BTreeMap<String, String> {
+    let mut used: BTreeSet<String> = RESERVED_TOKENS.iter().map(|v| v.to_string()).collect();
+
+    get_token_names(&g.terms, &[])
+        .into_iter()
+        .map(|(term, name)| {
+            let mut upper = String::new();
+            let mut previous: Option<char> = None;
+
+            for c in name.chars() {
+                if c.is_ascii_uppercase() && matches!(previous, Some(v) if v.is_ascii_lowercase() || v.is_ascii_digit()) {
+                    upper.push('_');
+                }
+                upper.push(c.to_ascii_uppercase());
+                previous = Some(c);
+            }
+
+            (term, unique_name(upper, &mut used))
+        })
+        .collect()
+}
+
+// Right sides of every non-terminal, start symbol goes first and others
+// follow in order of their first appearance as a left side
+fn group_alternatives(g: &Grammar) -> Vec<(&String, Vec<Vec<Symbol>>)> {
+    let mut order: Vec<String> = vec![g.start.clone()];
+    order.extend(get_production_order(g).into_iter().filter(|v| *v != g.start));
+
+    order
+        .iter()
+        .filter_map(|non_term| g.non_terms.get(non_term))
+        .map(|non_term| {
+            let alternatives = g
+                .productions
+                .iter()
+                .filter(|prod| prod.replaced_symbol.value == *non_term)
+                .map(|prod| prod.expression.clone())
+                .collect();
+            (non_term, alternatives)
+        })
+        .collect()
+}
+
+// Names of the symbols without epsilon
+fn symbol_names(
+    symbols: &[Symbol],
+    tokens: &BTreeMap<String, String>,
+    rules: &BTreeMap<String, String>,
+) -> Vec<String> {
+    symbols
+        .iter()
+        .filter(|v| v.kind != SymbolsKind::EPSILON)
+        .map(|v| match v.kind {
+            SymbolsKind::NONTERM => rules[&v.value].clone(),
+            _ => tokens[&v.value].clone(),
+        })
+        .collect()
+}
+
+// Literal in the quotes with backslash escapes
+fn quote(s: &str, quote: char) -> String {
+    let mut result = String::new();
+    result.push(quote);
+    for c in s.chars() {
+        if c == quote || c == '\\' {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+    result.push(quote);
+
+    result
+}
+
+// W3C EBNF has no escapes, literal is quoted with the quote it doesn't contain
+fn quote_w3c(s: &str) -> Result<String, Box<dyn Error>> {
+    match (s.contains('\''), s.contains('"')) {
+        _ if s.is_empty() => Err("Empty terminal can't be written in W3C EBNF".into()),
+        (true, true) => Err(format!("Terminal {} with both kinds of quotes can't be written in W3C EBNF", s).into()),
+        (true, _) => Ok(format!("\"{}\"", s)),
+        _ => Ok(format!("'{}'", s)),
+    }
+}
+
+// Name which needs no renaming: a letter or _ followed by letters, digits, _, - or .
+fn is_plain_name(s: &str) -> bool {
+    let mut chars = s.chars();
+
+    match chars.next() {
+        Some(c) if c.is_alphabetic() || c == '_' => (),
+        _ => return false,
+    }
+
+    chars.all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar::bnf;
+    use crate::grammar::earley::Chart;
+
+    #[test]
+    fn w3c_renamed_names_do_not_take_plain_names() {
+        let g = bnf::parse("S -> A' a_prime\nA' -> x\na_prime -> y").unwrap();
+
+        let ebnf = to_w3c_ebnf(&g).unwrap();
+
+        assert!(ebnf.contains("S        ::= a_prime1 a_prime"), "{}", ebnf);
+        assert!(ebnf.contains("a_prime1 ::= 'x'"), "{}", ebnf);
+        assert!(ebnf.contains("a_prime  ::= 'y'"), "{}", ebnf);
+    }
+
+    #[test]
+    fn reserved_words_are_not_used_as_names() {
+        let g = bnf::parse("error -> grammar eof EOF ws\ngrammar -> x").unwrap();
+
+        let bison = to_bison(&g);
+        assert!(bison.contains("%start error1"), "{}", bison);
+        assert!(bison.contains("    : grammar EOF2 EOF1 WS1"), "{}", bison);
+
+        let antlr = to_antlr(&g, "Test");
+        assert!(antlr.contains("\nerror\n    : grammar1 EOF2 EOF1 WS1"), "{}", antlr);
+    }
+
+    // Sentences up to the length accepted by the grammar
+    fn accepted(g: &Grammar, max_len: usize) -> BTreeSet<Vec<String>> {
+        let mut sentences: Vec<Vec<String>> = vec![vec![]];
+        let mut result: BTreeSet<Vec<String>> = BTreeSet::new();
+
+        for _ in 0..=max_len {
+            result.extend(sentences.iter().filter(|v| Chart::new(g, v).accepts()).cloned());
+            sentences = sentences
+                .iter()
+                .flat_map(|prefix| g.terms.iter().map(move |term| [prefix.clone(), vec![term.clone()]].concat()))
+                .collect();
+        }
+
+        result
+    }
+
+    #[test]
+    fn w3c_ebnf_is_read_back() {
+        let g = bnf::parse("E -> T E'\nE' -> \"+\" T E' | &\nT -> \"(\" E \")\" | id | \"it's\"").unwrap();
+
+        let ebnf = to_w3c_ebnf(&g).unwrap();
+        assert!(ebnf.starts_with("/* Generated by cfg2proper */"), "{}", ebnf);
+        assert!(ebnf.contains("( '+' T e_prime )?"), "{}", ebnf);
+
+        let read = bnf::parse(&ebnf).unwrap();
+        assert_eq!(read.terms, g.terms);
+        assert_eq!(accepted(&read, 4), accepted(&g, 4));
+    }
+
+    #[test]
+    fn w3c_ebnf_rejects_inexpressible_grammars() {
+        let g = bnf::parse("S -> a'b\"c").unwrap();
+        assert!(to_w3c_ebnf(&g).is_err());
+
+        let g = bnf::parse("S -> a A\nA -> &").unwrap();
+        assert!(to_w3c_ebnf(&g).is_err());
+
+        assert!(quote_w3c("").is_err());
+        assert_eq!(quote_w3c("it's").unwrap(), "\"it's\"");
+    }
+}
@@ -0,0 +1,320 @@
+use crate::grammar::transformations::{get_nullable_non_terms, get_productive, get_reachable};
+use crate::grammar::{Grammar, Symbol, SymbolVec, SymbolsKind};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt::Display;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LintKind {
+    Unproductive,
+    Unreachable,
+    UnusedTerm,
+    Cycle,
+    LeftRecursion,
+    NullableStart,
+}
+
+/// Problem found in the grammar
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub kind: LintKind,
+    pub message: String,
+}
+
+// Step of derivation: production index and position of the next symbol
+// within the production body
+type Step = (usize, usize);
+
+/// Returns problems of the grammar ordered by severity. Unproductive
+/// non-terminals and cycles are errors, unreachable and unused symbols are
+/// warnings, left recursion and nullable start symbol are informational
+///
+/// # Arguments
+///
+/// * `g` - Grammar to check
+pub fn lint(g: &Grammar) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = vec![];
+    let nullable = get_nullable_non_terms(g);
+
+    let productive = get_productive(g);
+    for non_term in g.non_terms.iter().filter(|v| !productive.contains(*v)) {
+        diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            kind: LintKind::Unproductive,
+            message: format!("non-terminal {} is unproductive, it derives no terminal string", non_term),
+        });
+    }
+
+    let used_terms: BTreeSet<&String> = g
+        .productions
+        .iter()
+        .flat_map(|prod| prod.expression.iter())
+        .filter(|v| v.kind == SymbolsKind::TERM)
+        .map(|v| &v.value)
+        .collect();
+
+    let reachable = get_reachable(g);
+    for non_term in g.non_terms.iter().filter(|v| !reachable.contains(*v)) {
+        diagnostics.push(Diagnostic {
+            severity: Severity::Warning,
+            kind: LintKind::Unreachable,
+            message: format!("non-terminal {} is unreachable from start symbol {}", non_term, g.start),
+        });
+    }
+    for term in used_terms.iter().filter(|v| !reachable.contains(**v)) {
+        diagnostics.push(Diagnostic {
+            severity: Severity::Warning,
+            kind: LintKind::Unreachable,
+            message: format!("terminal {} is unreachable from start symbol {}", term, g.start),
+        });
+    }
+
+    for term in g.terms.iter().filter(|v| !used_terms.contains(v)) {
+        diagnostics.push(Diagnostic {
+            severity: Severity::Warning,
+            kind: LintKind::UnusedTerm,
+            message: format!("terminal {} is declared but never used", term),
+        });
+    }
+
+    // A =>+ A through productions A -> alpha B beta with nullable alpha and beta
+    let cycle_edges = get_edges(g, &nullable, true);
+    for (non_term, path) in find_cycles(g, &cycle_edges) {
+        diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            kind: LintKind::Cycle,
+            message: format!(
+                "non-terminal {} derives itself, grammar is ambiguous: {}",
+                non_term,
+                derivation_chain(g, &non_term, &path, true)
+            ),
+        });
+    }
+
+    // A =>+ A gamma through productions A -> alpha B beta with nullable alpha
+    let lr_edges = get_edges(g, &nullable, false);
+    for (non_term, path) in find_cycles(g, &lr_edges) {
+        // Left recursion hidden behind nullable symbols is reported as indirect
+        let kind = match path.as_slice() {
+            [(_, 0)] => "direct",
+            _ => "indirect",
+        };
+
+        diagnostics.push(Diagnostic {
+            severity: Severity::Info,
+            kind: LintKind::LeftRecursion,
+            message: format!(
+                "{} left recursion on {}, grammar is not LL: {}",
+                kind,
+                non_term,
+                derivation_chain(g, &non_term, &path, false)
+            ),
+        });
+    }
+
+    if nullable.contains(&g.start) {
+        diagnostics.push(Diagnostic {
+            severity: Severity::Info,
+            kind: LintKind::NullableStart,
+            message: format!("start symbol {} derives empty string", g.start),
+        });
+    }
+
+    diagnostics.sort_by_key(|v| v.severity);
+
+    diagnostics
+}
+
+// Right side of the production without epsilon symbols
+fn body(g: &Grammar, production: usize) -> Vec<Symbol> {
+    g.productions[production]
+        .expression
+        .iter()
+        .filter(|v| v.kind != SymbolsKind::EPSILON)
+        .cloned()
+        .collect()
+}
+
+fn is_nullable(symbol: &Symbol, nullable: &BTreeSet<String>) -> bool {
+    symbol.kind == SymbolsKind::NONTERM && nullable.contains(&symbol.value)
+}
+
+// Edges A -> B for every production A -> alpha B beta with nullable alpha
+// (and beta if `nullable_suffix` is set), labeled with the derivation step
+fn get_edges(g: &Grammar, nullable: &BTreeSet<String>, nullable_suffix: bool) -> BTreeMap<String, Vec<(String, Step)>> {
+    let mut edges: BTreeMap<String, Vec<(String, Step)>> = BTreeMap::new();
+
+    for (production, prod) in g.productions.iter().enumerate() {
+        let body = body(g, production);
+
+        for (i, symbol) in body.iter().enumerate() {
+            let suffix_erased = !nullable_suffix || body[i + 1..].iter().all(|v| is_nullable(v, nullable));
+
+            if symbol.kind == SymbolsKind::NONTERM && suffix_erased {
+                edges
+                    .entry(prod.replaced_symbol.value.clone())
+                    .or_default()
+                    .push((symbol.value.clone(), (production, i)));
+            }
+
+            if !is_nullable(symbol, nullable) {
+                break;
+            }
+        }
+    }
+
+    edges
+}
+
+// Shortest cycle through every non-terminal for every production closing
+// it, so direct recursion isn't hidden by a longer one. Cycles over the same
+// productions are reported once
+fn find_cycles(g: &Grammar, edges: &BTreeMap<String, Vec<(String, Step)>>) -> Vec<(String, Vec<Step>)> {
+    let mut result: Vec<(String, Vec<Step>)> = vec![];
+    let mut reported: BTreeSet<BTreeSet<usize>> = BTreeSet::new();
+
+    for non_term in g.non_terms.iter() {
+        // Shortest paths from the non-terminal, visited in order of their length
+        let mut parents: BTreeMap<&String, (&String, Step)> = BTreeMap::new();
+        let mut visited: Vec<&String> = vec![non_term];
+        let mut queue: VecDeque<&String> = VecDeque::new();
+        queue.push_back(non_term);
+
+        while let Some(current) = queue.pop_front() {
+            for (next, step) in edges.get(current).into_iter().flatten() {
+                if next != non_term && !parents.contains_key(next) {
+                    parents.insert(next, (current, *step));
+                    visited.push(next);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        for current in visited.into_iter() {
+            let closing = edges.get(current).into_iter().flatten().filter(|(next, _)| next == non_term);
+
+            for (_, step) in closing {
+                let mut path = vec![*step];
+                let mut node = current;
+                while node != non_term {
+                    let (parent, step) = parents[node];
+                    path.push(step);
+                    node = parent;
+                }
+                path.reverse();
+
+                let productions: BTreeSet<usize> = path.iter().map(|(production, _)| *production).collect();
+                if reported.insert(productions) {
+                    result.push((non_term.clone(), path));
+                }
+            }
+        }
+    }
+
+    result
+}
+
+// Leftmost sentential forms of the derivation, nullable symbols before the
+// next expanded symbol (and after it if `nullable_suffix` is set) are erased
+// in a separate =>* step
+fn derivation_chain(
+    g: &Grammar,
+    start: &str,
+    path: &[Step],
+    nullable_suffix: bool,
+) -> String {
+    let mut form: Vec<Symbol> = vec![Symbol { kind: SymbolsKind::NONTERM, value: start.to_string() }];
+    let mut chain = start.to_string();
+
+    for (production, i) in path.iter() {
+        let body = body(g, *production);
+        let rest: Vec<Symbol> = form[1..].to_vec();
+
+        form = body.iter().chain(rest.iter()).cloned().collect();
+        chain += &format!(" => {}", SymbolVec(&form));
+
+        let erase_suffix = nullable_suffix && *i + 1 < form.len();
+        if *i > 0 || erase_suffix {
+            let end = match erase_suffix {
+                true => *i + 1,
+                _ => form.len(),
+            };
+            form = form[*i..end].to_vec();
+            chain += &format!(" =>* {}", SymbolVec(&form));
+        }
+    }
+
+    chain
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Info => write!(f, "info"),
+        }
+    }
+}
+
+impl Display for LintKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            LintKind::Unproductive => "unproductive",
+            LintKind::Unreachable => "unreachable",
+            LintKind::UnusedTerm => "unused-term",
+            LintKind::Cycle => "cycle",
+            LintKind::LeftRecursion => "left-recursion",
+            LintKind::NullableStart => "nullable-start",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity, self.kind, self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar::bnf;
+
+    fn messages(content: &str, kind: LintKind) -> Vec<String> {
+        lint(&bnf::parse(content).unwrap())
+            .into_iter()
+            .filter(|v| v.kind == kind)
+            .map(|v| v.message)
+            .collect()
+    }
+
+    #[test]
+    fn direct_recursion_is_not_hidden_by_nullable_prefix() {
+        let found = messages("S -> A S b | S S\nA -> a | &", LintKind::LeftRecursion);
+
+        assert_eq!(
+            found,
+            [
+                "indirect left recursion on S, grammar is not LL: S => A S b =>* S b",
+                "direct left recursion on S, grammar is not LL: S => S S",
+            ]
+        );
+    }
+
+    #[test]
+    fn mutual_recursion_is_reported_once() {
+        let found = messages("S -> A a | b\nA -> S c", LintKind::LeftRecursion);
+
+        assert_eq!(found, ["indirect left recursion on A, grammar is not LL: A => S c => A a c"]);
+    }
+}
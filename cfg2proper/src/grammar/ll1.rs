@@ -0,0 +1,204 @@
+use crate::grammar::sets::FirstFollow;
+use crate::grammar::{Grammar, Production, SymbolVec, END_MARKER_SYMBOL, EPSILON_SYMBOL};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+/// Cell of predictive parse table. Cell with more than one production is a conflict
+#[derive(Debug, Serialize)]
+pub struct LL1Entry {
+    #[serde(rename = "nonterm")]
+    pub non_term: String,
+    #[serde(rename = "term")]
+    pub term: String,
+    #[serde(rename = "productions")]
+    pub productions: Vec<Production>,
+}
+
+/// Predictive parse table is represented here
+#[derive(Debug, Serialize)]
+pub struct LL1Table {
+    #[serde(rename = "nonterms")]
+    pub non_terms: Vec<String>,
+    #[serde(rename = "terms")]
+    pub terms: Vec<String>,
+    #[serde(rename = "startsymbol")]
+    pub start: String,
+    #[serde(rename = "entries")]
+    pub entries: Vec<LL1Entry>,
+    #[serde(rename = "ll1")]
+    pub is_ll1: bool,
+}
+
+impl LL1Table {
+    /// Returns predictive parse table of the grammar
+    ///
+    /// # Arguments
+    ///
+    /// * `g` - Grammar to build table for
+    ///
+    /// * `sets` - FIRST and FOLLOW sets of the grammar
+    pub fn new(g: &Grammar, sets: &FirstFollow) -> LL1Table {
+        let epsilon = EPSILON_SYMBOL.to_string();
+
+        let mut cells: BTreeMap<(String, String), Vec<Production>> = BTreeMap::new();
+
+        for prod in g.productions.iter() {
+            // A -> alpha is placed into [A, a] for every a in FIRST(alpha),
+            // and into [A, b] for every b in FOLLOW(A) if alpha is nullable
+            let mut lookaheads = sets.first_of(&prod.expression);
+            if lookaheads.remove(&epsilon) {
+                lookaheads.extend(
+                    sets.follow
+                        .get(&prod.replaced_symbol.value)
+                        .cloned()
+                        .unwrap_or_default(),
+                );
+            }
+
+            for term in lookaheads.into_iter() {
+                let cell = cells
+                    .entry((prod.replaced_symbol.value.clone(), term))
+                    .or_default();
+                if !cell.contains(prod) {
+                    cell.push(prod.clone());
+                }
+            }
+        }
+
+        let entries: Vec<LL1Entry> = cells
+            .into_iter()
+            .map(|((non_term, term), productions)| LL1Entry {
+                non_term,
+                term,
+                productions,
+            })
+            .collect();
+
+        let mut non_terms: Vec<String> = g.non_terms.iter().cloned().collect();
+        non_terms.sort();
+
+        let mut terms: Vec<String> = g.terms.iter().cloned().collect();
+        terms.sort();
+        terms.push(END_MARKER_SYMBOL.to_string());
+
+        let is_ll1 = entries.iter().all(|entry| entry.productions.len() < 2);
+
+        LL1Table {
+            non_terms,
+            terms,
+            start: g.start.clone(),
+            entries,
+            is_ll1,
+        }
+    }
+
+    /// Returns productions placed into [non_term, term] cell
+    pub fn get(&self, non_term: &str, term: &str) -> Option<&Vec<Production>> {
+        self.entries
+            .iter()
+            .find(|entry| entry.non_term == non_term && entry.term == term)
+            .map(|entry| &entry.productions)
+    }
+
+    /// Returns cells containing competing productions
+    pub fn conflicts(&self) -> Vec<&LL1Entry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.productions.len() > 1)
+            .collect()
+    }
+}
+
+impl Display for LL1Table {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut rows: Vec<Vec<String>> = vec![];
+
+        let mut header = vec![String::new()];
+        header.extend(self.terms.iter().cloned());
+        rows.push(header);
+
+        for non_term in self.non_terms.iter() {
+            let mut row = vec![non_term.clone()];
+            for term in self.terms.iter() {
+                let cell = self.get(non_term, term).map_or(vec![], |prods| {
+                    prods
+                        .iter()
+                        .map(|prod| format!("{}", SymbolVec(&prod.expression)))
+                        .collect()
+                });
+                row.push(cell.join(" / "));
+            }
+            rows.push(row);
+        }
+
+        let mut widths = vec![0; rows[0].len()];
+        for row in rows.iter() {
+            for (i, cell) in row.iter().enumerate() {
+                widths[i] = widths[i].max(cell.chars().count());
+            }
+        }
+
+        for row in rows.iter() {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(widths.iter())
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect();
+            writeln!(f, "{}", cells.join(" | ").trim_end())?;
+        }
+
+        let conflicts = self.conflicts();
+        if conflicts.is_empty() {
+            return writeln!(f, "\nGrammar is LL(1)");
+        }
+
+        writeln!(f, "\nGrammar is not LL(1), conflicts:")?;
+        for entry in conflicts.into_iter() {
+            let prods: Vec<String> = entry.productions.iter().map(|prod| prod.to_string()).collect();
+            writeln!(f, "[{}, {}]: {}", entry.non_term, entry.term, prods.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar::bnf;
+
+    fn table(content: &str) -> LL1Table {
+        let g = bnf::parse(content).unwrap();
+
+        LL1Table::new(&g, &FirstFollow::new(&g))
+    }
+
+    fn rhs(prods: &[Production]) -> Vec<String> {
+        prods.iter().map(|prod| SymbolVec(&prod.expression).to_string()).collect()
+    }
+
+    #[test]
+    fn expression_grammar_is_ll1() {
+        let table = table("E -> T E'\nE' -> \"+\" T E' | &\nT -> \"(\" E \")\" | id");
+
+        assert!(table.is_ll1);
+        assert!(table.conflicts().is_empty());
+        assert_eq!(rhs(table.get("E'", "+").unwrap()), ["+ T E'"]);
+        assert_eq!(rhs(table.get("E'", ")").unwrap()), ["&"]);
+        assert_eq!(rhs(table.get("E'", "$").unwrap()), ["&"]);
+        assert!(table.get("E", "+").is_none());
+    }
+
+    #[test]
+    fn common_prefix_is_a_conflict() {
+        let table = table("S -> if E then S | if E then S else S | a\nE -> b");
+
+        assert!(!table.is_ll1);
+
+        let conflicts = table.conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!((conflicts[0].non_term.as_str(), conflicts[0].term.as_str()), ("S", "if"));
+        assert_eq!(rhs(&conflicts[0].productions), ["if E then S", "if E then S else S"]);
+    }
+}
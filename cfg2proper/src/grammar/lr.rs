@@ -0,0 +1,881 @@
+use crate::grammar::sets::FirstFollow;
+use crate::grammar::transformations::get_fresh_out_of;
+use crate::grammar::{Grammar, Origin, Production, Symbol, SymbolsKind, SymbolVec, END_MARKER_SYMBOL, EPSILON_SYMBOL};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt::Display;
+use std::fs::File;
+use std::str::FromStr;
+
+/// LR(0) item: production index within the augmented grammar and position
+/// of the dot within its right side without epsilon symbols
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Lr0Item {
+    pub production: usize,
+    pub dot: usize,
+}
+
+/// Grammar augmented with S' -> S production, which always has index 0
+#[derive(Debug)]
+pub struct AugmentedGrammar {
+    pub productions: Vec<Production>,
+    pub terms: Vec<String>,
+    pub non_terms: Vec<String>,
+    // Right sides of the productions without epsilon symbols
+    bodies: Vec<Vec<Symbol>>,
+}
+
+/// Canonical collection of LR(0) item sets with goto transitions
+#[derive(Debug)]
+pub struct Lr0Automaton {
+    pub grammar: AugmentedGrammar,
+    pub states: Vec<Vec<Lr0Item>>,
+    pub goto: Vec<BTreeMap<String, usize>>,
+}
+
+/// LR(1) item set: LR(0) items with their lookahead terminals
+pub type Lr1State = BTreeMap<Lr0Item, BTreeSet<String>>;
+
+/// Collection of LR(1) item sets with goto transitions, either canonical
+/// or with states of the same core merged (LALR(1))
+#[derive(Debug)]
+pub struct Lr1Automaton {
+    pub grammar: AugmentedGrammar,
+    pub method: LrMethod,
+    pub states: Vec<Lr1State>,
+    pub goto: Vec<BTreeMap<String, usize>>,
+}
+
+/// Parse table construction method
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LrMethod {
+    Lr0,
+    Slr1,
+    Lalr1,
+    Lr1,
+}
+
+impl FromStr for LrMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lr0" => Ok(LrMethod::Lr0),
+            "slr1" => Ok(LrMethod::Slr1),
+            "lalr1" => Ok(LrMethod::Lalr1),
+            "lr1" => Ok(LrMethod::Lr1),
+            _ => Err(String::from("Unknown LR method ") + s + ", expected one of lr0, slr1, lalr1, lr1"),
+        }
+    }
+}
+
+impl Display for LrMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LrMethod::Lr0 => write!(f, "LR(0)"),
+            LrMethod::Slr1 => write!(f, "SLR(1)"),
+            LrMethod::Lalr1 => write!(f, "LALR(1)"),
+            LrMethod::Lr1 => write!(f, "LR(1)"),
+        }
+    }
+}
+
+/// Parse table action
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", content = "target", rename_all = "lowercase")]
+pub enum Action {
+    Shift(usize),
+    Reduce(usize),
+    Accept,
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Shift(state) => write!(f, "s{}", state),
+            Action::Reduce(production) => write!(f, "r{}", production),
+            Action::Accept => write!(f, "acc"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConflictKind {
+    ShiftReduce,
+    ReduceReduce,
+}
+
+/// Cell of ACTION table with more than one action and the items causing them
+#[derive(Debug)]
+pub struct LrConflict {
+    pub state: usize,
+    pub term: String,
+    pub kind: ConflictKind,
+    pub actions: Vec<Action>,
+    pub items: Vec<String>,
+}
+
+/// Parse table which can't be built or used for parsing
+#[derive(Debug)]
+pub enum LrError {
+    /// Table of the method can't be built of the automaton
+    UnsupportedMethod(LrMethod),
+    /// Table of the method has cells with more than one action
+    Conflicts(LrMethod, Vec<LrConflict>),
+}
+
+/// Conflicts of the tables built with SLR(1), LALR(1) and LR(1) methods
+#[derive(Debug)]
+pub struct LrComparison {
+    pub levels: Vec<(LrMethod, Vec<LrConflict>)>,
+}
+
+/// ACTION/GOTO parse table. ACTION cell with more than one action is a conflict.
+/// Parsing starts in state 0, epsilon symbols of productions aren't pushed
+/// to the stack
+#[derive(Debug, Serialize)]
+pub struct LrTable {
+    #[serde(rename = "method")]
+    pub method: LrMethod,
+    #[serde(rename = "productions")]
+    pub productions: Vec<Production>,
+    #[serde(rename = "terms")]
+    pub terms: Vec<String>,
+    #[serde(rename = "nonterms")]
+    pub non_terms: Vec<String>,
+    #[serde(rename = "action")]
+    pub action: Vec<BTreeMap<String, Vec<Action>>>,
+    #[serde(rename = "goto")]
+    pub goto: Vec<BTreeMap<String, usize>>,
+    // Items of every state to explain conflicts
+    #[serde(skip)]
+    pub state_items: Vec<Vec<Lr0Item>>,
+}
+
+impl AugmentedGrammar {
+    /// Returns the grammar augmented with a fresh start symbol
+    pub fn new(g: &Grammar) -> AugmentedGrammar {
+        let mut used_names: BTreeSet<String> = g.non_terms.union(&g.terms).cloned().collect();
+        let start = get_fresh_out_of(&g.start, &mut used_names);
+
+        let mut productions = vec![Production {
+            replaced_symbol: Symbol { kind: SymbolsKind::NONTERM, value: start },
+            expression: vec![Symbol { kind: SymbolsKind::NONTERM, value: g.start.clone() }],
+            origin: Origin::default().derived("augment"),
+        }];
+        productions.extend(g.productions.iter().cloned());
+
+        let bodies = productions.iter().map(body_of).collect();
+
+        let mut terms: Vec<String> = g.terms.iter().cloned().collect();
+        terms.push(END_MARKER_SYMBOL.to_string());
+
+        AugmentedGrammar {
+            productions,
+            terms,
+            non_terms: g.non_terms.iter().cloned().collect(),
+            bodies,
+        }
+    }
+
+    /// Returns symbol after the dot, None if the item is complete
+    pub fn next_symbol(&self, item: &Lr0Item) -> Option<&Symbol> {
+        self.bodies[item.production].get(item.dot)
+    }
+
+    /// Returns the item written as A -> alpha • beta
+    pub fn item_to_string(&self, item: &Lr0Item) -> String {
+        item_to_string(&self.productions, item)
+    }
+}
+
+// Right side of the production without epsilon symbols
+fn body_of(prod: &Production) -> Vec<Symbol> {
+    prod.expression
+        .iter()
+        .filter(|v| v.kind != SymbolsKind::EPSILON)
+        .cloned()
+        .collect()
+}
+
+fn item_to_string(productions: &[Production], item: &Lr0Item) -> String {
+    let body = body_of(&productions[item.production]);
+    let (before, after) = body.split_at(item.dot);
+
+    let parts: Vec<String> = vec![
+        SymbolVec(&before.to_vec()).to_string(),
+        String::from("•"),
+        SymbolVec(&after.to_vec()).to_string(),
+    ];
+
+    format!(
+        "{} -> {}",
+        productions[item.production].replaced_symbol.value,
+        parts.into_iter().filter(|v| !v.is_empty()).collect::<Vec<_>>().join(" ")
+    )
+}
+
+impl Lr0Automaton {
+    /// Returns canonical collection of LR(0) item sets of the grammar.
+    /// State 0 is the closure of S' -> • S
+    pub fn new(g: &Grammar) -> Lr0Automaton {
+        let grammar = AugmentedGrammar::new(g);
+
+        let mut automaton = Lr0Automaton {
+            states: vec![],
+            goto: vec![],
+            grammar,
+        };
+
+        let mut index: BTreeMap<Vec<Lr0Item>, usize> = BTreeMap::new();
+
+        let kernel = vec![Lr0Item { production: 0, dot: 0 }];
+        index.insert(kernel.clone(), 0);
+        automaton.states.push(automaton.closure(kernel));
+        automaton.goto.push(BTreeMap::new());
+
+        let mut row = 0;
+
+        while row < automaton.states.len() {
+            for (symbol, kernel) in automaton.successors(row).into_iter() {
+                let target = match index.get(&kernel) {
+                    Some(v) => *v,
+                    _ => {
+                        index.insert(kernel.clone(), automaton.states.len());
+                        automaton.states.push(automaton.closure(kernel));
+                        automaton.goto.push(BTreeMap::new());
+                        automaton.states.len() - 1
+                    }
+                };
+
+                automaton.goto[row].insert(symbol, target);
+            }
+
+            row += 1;
+        }
+
+        automaton
+    }
+
+    /// Returns ACTION/GOTO table built with the given method, LR(0) reduces
+    /// on every terminal and SLR(1) on FOLLOW set of the production left side
+    ///
+    /// # Arguments
+    ///
+    /// * `g` - Grammar the automaton was built of
+    ///
+    /// * `method` - Either LR(0) or SLR(1)
+    pub fn table(&self, g: &Grammar, method: LrMethod) -> Result<LrTable, LrError> {
+        if method != LrMethod::Lr0 && method != LrMethod::Slr1 {
+            return Err(LrError::UnsupportedMethod(method));
+        }
+
+        let follow = FirstFollow::new(g).follow;
+
+        Ok(LrTable::fill(&self.grammar, method, &self.states, &self.goto, |_, item| {
+            let lhs = &self.grammar.productions[item.production].replaced_symbol.value;
+            match method {
+                LrMethod::Slr1 => follow.get(lhs).map(|v| v.iter().cloned().collect()).unwrap_or_default(),
+                _ => self.grammar.terms.clone(),
+            }
+        }))
+    }
+
+    /// Renders the automaton in dot format to the file
+    pub fn render_to(&self, output: &str) {
+        let mut f = File::create(output).unwrap();
+        dot::render(self, &mut f).unwrap()
+    }
+
+    fn closure(&self, kernel: Vec<Lr0Item>) -> Vec<Lr0Item> {
+        let mut items = kernel;
+        let mut i = 0;
+
+        while i < items.len() {
+            if let Some(symbol) = self.grammar.next_symbol(&items[i]).cloned() {
+                if symbol.kind == SymbolsKind::NONTERM {
+                    for (production, prod) in self.grammar.productions.iter().enumerate() {
+                        let item = Lr0Item { production, dot: 0 };
+                        if prod.replaced_symbol.value == symbol.value && !items.contains(&item) {
+                            items.push(item);
+                        }
+                    }
+                }
+            }
+
+            i += 1;
+        }
+
+        items
+    }
+
+    // Kernels of goto(state, X) for every symbol X after a dot in the state,
+    // symbols are taken in order of their appearance
+    fn successors(&self, state: usize) -> Vec<(String, Vec<Lr0Item>)> {
+        let mut result: Vec<(String, Vec<Lr0Item>)> = vec![];
+
+        for item in self.states[state].iter() {
+            let symbol = match self.grammar.next_symbol(item) {
+                Some(v) => v.value.clone(),
+                None => continue,
+            };
+
+            let moved = Lr0Item { dot: item.dot + 1, ..*item };
+
+            match result.iter_mut().find(|v| v.0 == symbol) {
+                Some(v) => v.1.push(moved),
+                None => result.push((symbol, vec![moved])),
+            }
+        }
+
+        for (_, kernel) in result.iter_mut() {
+            kernel.sort();
+        }
+
+        result
+    }
+}
+
+impl Lr1Automaton {
+    /// Returns canonical collection of LR(1) item sets of the grammar.
+    /// State 0 is the closure of [S' -> • S, $]
+    pub fn new(g: &Grammar) -> Lr1Automaton {
+        let sets = FirstFollow::new(g);
+
+        let mut automaton = Lr1Automaton {
+            grammar: AugmentedGrammar::new(g),
+            method: LrMethod::Lr1,
+            states: vec![],
+            goto: vec![],
+        };
+
+        let mut index: BTreeMap<Lr1State, usize> = BTreeMap::new();
+
+        let mut kernel = Lr1State::new();
+        kernel.insert(Lr0Item { production: 0, dot: 0 }, vec![END_MARKER_SYMBOL.to_string()].into_iter().collect());
+        index.insert(kernel.clone(), 0);
+        automaton.states.push(automaton.closure(kernel, &sets));
+        automaton.goto.push(BTreeMap::new());
+
+        let mut row = 0;
+
+        while row < automaton.states.len() {
+            for (symbol, kernel) in automaton.successors(row).into_iter() {
+                let target = match index.get(&kernel) {
+                    Some(v) => *v,
+                    _ => {
+                        index.insert(kernel.clone(), automaton.states.len());
+                        automaton.states.push(automaton.closure(kernel, &sets));
+                        automaton.goto.push(BTreeMap::new());
+                        automaton.states.len() - 1
+                    }
+                };
+
+                automaton.goto[row].insert(symbol, target);
+            }
+
+            row += 1;
+        }
+
+        automaton
+    }
+
+    /// Returns LALR(1) automaton of the grammar: canonical LR(1) states
+    /// with the same LR(0) core are merged and their lookaheads united
+    pub fn lalr(g: &Grammar) -> Lr1Automaton {
+        let canonical = Lr1Automaton::new(g);
+
+        // Merged states are numbered in order of the first appearance of their core
+        let mut cores: BTreeMap<Vec<Lr0Item>, usize> = BTreeMap::new();
+        let mut merged_index: Vec<usize> = vec![];
+        let mut states: Vec<Lr1State> = vec![];
+
+        for state in canonical.states.iter() {
+            let core: Vec<Lr0Item> = state.keys().cloned().collect();
+            let next = states.len();
+            let i = *cores.entry(core).or_insert(next);
+
+            if i == next {
+                states.push(Lr1State::new());
+            }
+
+            for (item, lookaheads) in state.iter() {
+                states[i].entry(*item).or_default().extend(lookaheads.iter().cloned());
+            }
+
+            merged_index.push(i);
+        }
+
+        let mut goto: Vec<BTreeMap<String, usize>> = vec![BTreeMap::new(); states.len()];
+        for (state, transitions) in canonical.goto.iter().enumerate() {
+            for (symbol, target) in transitions.iter() {
+                goto[merged_index[state]].insert(symbol.clone(), merged_index[*target]);
+            }
+        }
+
+        Lr1Automaton {
+            grammar: canonical.grammar,
+            method: LrMethod::Lalr1,
+            states,
+            goto,
+        }
+    }
+
+    /// Returns ACTION/GOTO table reducing complete items on their lookaheads
+    pub fn table(&self) -> LrTable {
+        let cores: Vec<Vec<Lr0Item>> = self.states.iter().map(|v| v.keys().cloned().collect()).collect();
+
+        LrTable::fill(&self.grammar, self.method, &cores, &self.goto, |state, item| {
+            self.states[state][item].iter().cloned().collect()
+        })
+    }
+
+    /// Returns the item written as A -> alpha • beta, a/b
+    pub fn item_to_string(&self, item: &Lr0Item, lookaheads: &BTreeSet<String>) -> String {
+        let lookaheads: Vec<&str> = lookaheads.iter().map(|v| v.as_str()).collect();
+        format!("{}, {}", self.grammar.item_to_string(item), lookaheads.join("/"))
+    }
+
+    /// Renders the automaton in dot format to the file
+    pub fn render_to(&self, output: &str) {
+        let mut f = File::create(output).unwrap();
+        dot::render(self, &mut f).unwrap()
+    }
+
+    // Item [A -> alpha • B beta, a] adds [B -> • gamma, b] for every b in FIRST(beta a)
+    fn closure(&self, kernel: Lr1State, sets: &FirstFollow) -> Lr1State {
+        let epsilon = EPSILON_SYMBOL.to_string();
+        let mut items = kernel;
+        let mut queue: Vec<Lr0Item> = items.keys().cloned().collect();
+
+        while let Some(item) = queue.pop() {
+            let symbol = match self.grammar.next_symbol(&item) {
+                Some(v) if v.kind == SymbolsKind::NONTERM => v.value.clone(),
+                _ => continue,
+            };
+
+            let mut lookaheads = sets.first_of(&self.grammar.bodies[item.production][item.dot + 1..]);
+            if lookaheads.remove(&epsilon) {
+                lookaheads.extend(items[&item].iter().cloned());
+            }
+
+            for (production, prod) in self.grammar.productions.iter().enumerate() {
+                if prod.replaced_symbol.value != symbol {
+                    continue;
+                }
+
+                let new_item = Lr0Item { production, dot: 0 };
+                let entry = items.entry(new_item).or_default();
+                let before = entry.len();
+                entry.extend(lookaheads.iter().cloned());
+
+                if entry.len() > before {
+                    queue.push(new_item);
+                }
+            }
+        }
+
+        items
+    }
+
+    // Kernels of goto(state, X) for every symbol X after a dot in the state,
+    // symbols are taken in order of their appearance
+    fn successors(&self, state: usize) -> Vec<(String, Lr1State)> {
+        let mut result: Vec<(String, Lr1State)> = vec![];
+
+        for (item, lookaheads) in self.states[state].iter() {
+            let symbol = match self.grammar.next_symbol(item) {
+                Some(v) => v.value.clone(),
+                None => continue,
+            };
+
+            let moved = Lr0Item { dot: item.dot + 1, ..*item };
+
+            match result.iter_mut().find(|v| v.0 == symbol) {
+                Some(v) => {
+                    v.1.insert(moved, lookaheads.clone());
+                }
+                None => result.push((symbol, vec![(moved, lookaheads.clone())].into_iter().collect())),
+            }
+        }
+
+        result
+    }
+}
+
+/// Returns ACTION/GOTO table of the grammar built with the given method
+pub fn build_table(g: &Grammar, method: LrMethod) -> LrTable {
+    match method {
+        LrMethod::Lr0 | LrMethod::Slr1 => Lr0Automaton::new(g)
+            .table(g, method)
+            .expect("LR(0) automaton builds LR(0) and SLR(1) tables"),
+        LrMethod::Lalr1 => Lr1Automaton::lalr(g).table(),
+        LrMethod::Lr1 => Lr1Automaton::new(g).table(),
+    }
+}
+
+impl LrComparison {
+    /// Returns conflicts of SLR(1), LALR(1) and LR(1) tables of the grammar
+    pub fn new(g: &Grammar) -> LrComparison {
+        let levels = vec![LrMethod::Slr1, LrMethod::Lalr1, LrMethod::Lr1]
+            .into_iter()
+            .map(|method| (method, build_table(g, method).conflicts()))
+            .collect();
+
+        LrComparison { levels }
+    }
+
+    /// Returns the weakest method producing a table without conflicts
+    pub fn weakest_method(&self) -> Option<LrMethod> {
+        self.levels.iter().find(|v| v.1.is_empty()).map(|v| v.0)
+    }
+}
+
+impl LrTable {
+    fn new(grammar: &AugmentedGrammar, method: LrMethod) -> LrTable {
+        LrTable {
+            method,
+            productions: grammar.productions.clone(),
+            terms: grammar.terms.clone(),
+            non_terms: grammar.non_terms.clone(),
+            action: vec![],
+            goto: vec![],
+            state_items: vec![],
+        }
+    }
+
+    // Fills the table with accept action, reductions of complete items on
+    // the terminals returned by `lookaheads` and transitions of every state
+    fn fill<F>(
+        grammar: &AugmentedGrammar,
+        method: LrMethod,
+        states: &[Vec<Lr0Item>],
+        goto: &[BTreeMap<String, usize>],
+        lookaheads: F,
+    ) -> LrTable
+    where
+        F: Fn(usize, &Lr0Item) -> Vec<String>,
+    {
+        let mut table = LrTable::new(grammar, method);
+
+        for (state, items) in states.iter().enumerate() {
+            for item in items.iter() {
+                if grammar.next_symbol(item).is_some() {
+                    continue;
+                }
+
+                if item.production == 0 {
+                    table.add_action(state, END_MARKER_SYMBOL.to_string(), Action::Accept);
+                    continue;
+                }
+
+                for term in lookaheads(state, item).into_iter() {
+                    table.add_action(state, term, Action::Reduce(item.production));
+                }
+            }
+
+            table.add_transitions(state, &goto[state], grammar);
+            table.state_items.push(items.clone());
+        }
+
+        table
+    }
+
+    fn add_action(&mut self, state: usize, term: String, action: Action) {
+        while self.action.len() <= state {
+            self.action.push(BTreeMap::new());
+        }
+
+        let cell = self.action[state].entry(term).or_default();
+        if !cell.contains(&action) {
+            cell.push(action);
+        }
+    }
+
+    // Shifts on terminals go to ACTION table, transitions on non-terminals to GOTO table
+    fn add_transitions(&mut self, state: usize, transitions: &BTreeMap<String, usize>, grammar: &AugmentedGrammar) {
+        while self.action.len() <= state {
+            self.action.push(BTreeMap::new());
+        }
+        while self.goto.len() <= state {
+            self.goto.push(BTreeMap::new());
+        }
+
+        for (symbol, target) in transitions.iter() {
+            match grammar.non_terms.contains(symbol) {
+                true => {
+                    self.goto[state].insert(symbol.clone(), *target);
+                }
+                _ => self.add_action(state, symbol.clone(), Action::Shift(*target)),
+            }
+        }
+    }
+
+    /// Returns actions of [state, term] cell
+    pub fn get(&self, state: usize, term: &str) -> &[Action] {
+        self.action[state].get(term).map_or(&[], |v| v.as_slice())
+    }
+
+    /// Returns cells containing more than one action with the items causing them
+    pub fn conflicts(&self) -> Vec<LrConflict> {
+        let mut conflicts: Vec<LrConflict> = vec![];
+
+        for (state, cells) in self.action.iter().enumerate() {
+            for (term, actions) in cells.iter().filter(|v| v.1.len() > 1) {
+                let kind = match actions.iter().any(|v| matches!(v, Action::Shift(_))) {
+                    true => ConflictKind::ShiftReduce,
+                    _ => ConflictKind::ReduceReduce,
+                };
+
+                let items = self.state_items[state]
+                    .iter()
+                    .filter(|item| self.is_involved(item, term, actions))
+                    .map(|item| item_to_string(&self.productions, item))
+                    .collect();
+
+                conflicts.push(LrConflict {
+                    state,
+                    term: term.clone(),
+                    kind,
+                    actions: actions.clone(),
+                    items,
+                });
+            }
+        }
+
+        conflicts
+    }
+
+    // Item is involved in conflict if it shifts the terminal or reduces by one
+    // of the conflicting productions
+    fn is_involved(&self, item: &Lr0Item, term: &str, actions: &[Action]) -> bool {
+        match body_of(&self.productions[item.production]).get(item.dot) {
+            Some(symbol) => symbol.value == term && actions.iter().any(|v| matches!(v, Action::Shift(_))),
+            None => actions.iter().any(|v| match v {
+                Action::Reduce(i) => *i == item.production,
+                Action::Accept => item.production == 0,
+                _ => false,
+            }),
+        }
+    }
+}
+
+impl LrConflict {
+    // Conflict is identified by its terminal and items regardless of the state
+    // number, which differs between tables built with different methods
+    fn signature(&self) -> (ConflictKind, &str, &[String]) {
+        (self.kind, self.term.as_str(), self.items.as_slice())
+    }
+
+    fn kind_name(&self) -> &'static str {
+        match self.kind {
+            ConflictKind::ShiftReduce => "shift/reduce",
+            ConflictKind::ReduceReduce => "reduce/reduce",
+        }
+    }
+}
+
+impl Display for LrConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let actions: Vec<String> = self.actions.iter().map(|v| v.to_string()).collect();
+
+        write!(
+            f,
+            "state {}, {} on {}: {} ({})",
+            self.state,
+            self.kind_name(),
+            self.term,
+            actions.join(" / "),
+            self.items.join("; ")
+        )
+    }
+}
+
+impl Display for LrError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LrError::UnsupportedMethod(method) => write!(f, "{} table can't be built of LR(0) automaton", method),
+            LrError::Conflicts(method, conflicts) => {
+                write!(f, "Grammar is not {}, conflicts:", method)?;
+                for conflict in conflicts.iter() {
+                    write!(f, "\n{}", conflict)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for LrError {}
+
+impl Display for LrTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Productions:")?;
+        for (i, prod) in self.productions.iter().enumerate() {
+            writeln!(f, "{}: {}", i, prod)?;
+        }
+        writeln!(f)?;
+
+        let mut rows: Vec<Vec<String>> = vec![];
+
+        let mut header = vec![String::from("State")];
+        header.extend(self.terms.iter().cloned());
+        header.extend(self.non_terms.iter().cloned());
+        rows.push(header);
+
+        for state in 0..self.action.len() {
+            let mut row = vec![state.to_string()];
+            for term in self.terms.iter() {
+                let actions: Vec<String> = self.get(state, term).iter().map(|v| v.to_string()).collect();
+                row.push(actions.join("/"));
+            }
+            for non_term in self.non_terms.iter() {
+                row.push(self.goto[state].get(non_term).map_or(String::new(), |v| v.to_string()));
+            }
+            rows.push(row);
+        }
+
+        write_rows(f, &rows)?;
+
+        let conflicts = self.conflicts();
+        if conflicts.is_empty() {
+            return writeln!(f, "\nGrammar is {}", self.method);
+        }
+
+        writeln!(f, "\nGrammar is not {}, conflicts:", self.method)?;
+        for conflict in conflicts.iter() {
+            writeln!(f, "{}", conflict)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes rows as a table with aligned columns separated by |
+pub fn write_rows(f: &mut std::fmt::Formatter<'_>, rows: &[Vec<String>]) -> std::fmt::Result {
+    let mut widths: Vec<usize> = vec![];
+    for row in rows.iter() {
+        for (i, cell) in row.iter().enumerate() {
+            match widths.get_mut(i) {
+                Some(width) => *width = (*width).max(cell.chars().count()),
+                None => widths.push(cell.chars().count()),
+            }
+        }
+    }
+
+    for row in rows.iter() {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        writeln!(f, "{}", cells.join(" | ").trim_end())?;
+    }
+
+    Ok(())
+}
+
+impl Display for LrComparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut previous: Option<&Vec<LrConflict>> = None;
+
+        for (method, conflicts) in self.levels.iter() {
+            writeln!(f, "{}: {} conflict(s)", method, conflicts.len())?;
+
+            for conflict in conflicts.iter() {
+                writeln!(f, "  {}", conflict)?;
+            }
+
+            let current: BTreeSet<_> = conflicts.iter().map(|v| v.signature()).collect();
+            let mut resolved: Vec<&LrConflict> = vec![];
+            for conflict in previous.into_iter().flatten() {
+                let signature = conflict.signature();
+                if !current.contains(&signature) && !resolved.iter().any(|v| v.signature() == signature) {
+                    resolved.push(conflict);
+                }
+            }
+
+            for conflict in resolved.iter() {
+                writeln!(
+                    f,
+                    "  resolved: {} on {} ({})",
+                    conflict.kind_name(),
+                    conflict.term,
+                    conflict.items.join("; ")
+                )?;
+            }
+
+            previous = Some(conflicts);
+        }
+
+        match self.weakest_method() {
+            Some(method) => writeln!(f, "\nGrammar is {}", method),
+            None => writeln!(f, "\nGrammar is not LR(1)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar::bnf;
+
+    const EXPRESSION: &str = "E -> E \"+\" T | T\nT -> T \"*\" F | F\nF -> \"(\" E \")\" | id";
+    const ASSIGNMENT: &str = "S -> L \"=\" R | R\nL -> \"*\" R | id\nR -> L";
+
+    #[test]
+    fn expression_grammar_is_slr1_but_not_lr0() {
+        let g = bnf::parse(EXPRESSION).unwrap();
+        let automaton = Lr0Automaton::new(&g);
+
+        assert_eq!(automaton.states.len(), 12);
+        assert!(automaton.table(&g, LrMethod::Slr1).unwrap().conflicts().is_empty());
+        assert!(automaton.table(&g, LrMethod::Lalr1).is_err());
+
+        let conflicts = automaton.table(&g, LrMethod::Lr0).unwrap().conflicts();
+        assert!(!conflicts.is_empty());
+        assert!(conflicts.iter().all(|v| v.kind == ConflictKind::ShiftReduce));
+        assert!(conflicts.iter().any(|v| v.term == "*"));
+    }
+
+    #[test]
+    fn slr1_reduces_on_follow_of_assignment_target() {
+        let g = bnf::parse(ASSIGNMENT).unwrap();
+        let conflicts = build_table(&g, LrMethod::Slr1).conflicts();
+
+        // = is in FOLLOW(R) since S => L = R => * R = R
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].kind, ConflictKind::ShiftReduce);
+        assert_eq!(conflicts[0].term, "=");
+        assert_eq!(conflicts[0].items.len(), 2);
+    }
+
+    #[test]
+    fn lalr1_resolves_slr1_conflict_of_assignment() {
+        let g = bnf::parse(ASSIGNMENT).unwrap();
+
+        let lalr = Lr1Automaton::lalr(&g);
+        let lr1 = Lr1Automaton::new(&g);
+        assert!(lalr.table().conflicts().is_empty());
+        assert!(lr1.table().conflicts().is_empty());
+        assert_eq!(lalr.states.len(), Lr0Automaton::new(&g).states.len());
+        assert!(lr1.states.len() > lalr.states.len());
+
+        let comparison = LrComparison::new(&g);
+        assert_eq!(comparison.weakest_method(), Some(LrMethod::Lalr1));
+        assert!(comparison.to_string().contains("resolved: shift/reduce on ="));
+    }
+
+    #[test]
+    fn merging_lr1_states_may_add_reduce_reduce_conflict() {
+        let g = bnf::parse("S -> a A d | b B d | a B e | b A e\nA -> c\nB -> c").unwrap();
+
+        let conflicts = Lr1Automaton::lalr(&g).table().conflicts();
+        assert!(!conflicts.is_empty());
+        assert!(conflicts.iter().all(|v| v.kind == ConflictKind::ReduceReduce));
+        assert_eq!(LrComparison::new(&g).weakest_method(), Some(LrMethod::Lr1));
+    }
+}
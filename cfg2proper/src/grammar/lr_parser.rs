@@ -0,0 +1,224 @@
+use crate::grammar::lr::{write_rows, Action, LrError, LrTable};
+use crate::grammar::parse_tree::ParseTree;
+use crate::grammar::{Symbol, SymbolsKind, END_MARKER_SYMBOL, EPSILON_SYMBOL};
+use std::error::Error;
+use std::fmt::Display;
+
+/// Configuration of shift-reduce parser and the action taken in it,
+/// None action is a syntax error
+#[derive(Debug)]
+pub struct LrStep {
+    pub states: Vec<usize>,
+    pub symbols: Vec<String>,
+    pub input: Vec<String>,
+    pub action: Option<Action>,
+}
+
+/// Token which has no action in the current state
+#[derive(Debug)]
+pub struct SyntaxError {
+    pub position: usize,
+    pub found: String,
+    pub expected: Vec<String>,
+}
+
+/// Result of running the parse table over a sentence with every step taken
+#[derive(Debug)]
+pub struct LrParse<'a> {
+    pub steps: Vec<LrStep>,
+    pub result: Result<ParseTree, SyntaxError>,
+    table: &'a LrTable,
+}
+
+impl Display for SyntaxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "syntax error at token {} ({}), expected one of: {}",
+            self.position + 1,
+            self.found,
+            self.expected.join(" ")
+        )
+    }
+}
+
+impl Error for SyntaxError {}
+
+impl<'a> LrParse<'a> {
+    /// Returns the result of shift-reduce parsing of the sentence,
+    /// or conflicts of the table if it has any
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - ACTION/GOTO table without conflicts
+    ///
+    /// * `tokens` - Terminals of the sentence
+    pub fn new(table: &'a LrTable, tokens: &[String]) -> Result<LrParse<'a>, LrError> {
+        let conflicts = table.conflicts();
+        if !conflicts.is_empty() {
+            return Err(LrError::Conflicts(table.method, conflicts));
+        }
+
+        let mut input: Vec<String> = tokens.to_vec();
+        input.push(END_MARKER_SYMBOL.to_string());
+
+        let mut parse = LrParse {
+            steps: vec![],
+            result: Err(SyntaxError { position: 0, found: String::new(), expected: vec![] }),
+            table,
+        };
+
+        let mut states: Vec<usize> = vec![0];
+        let mut trees: Vec<ParseTree> = vec![];
+        let mut position = 0;
+
+        loop {
+            let state = *states.last().unwrap();
+            let action = table.get(state, &input[position]).first().cloned();
+
+            parse.steps.push(LrStep {
+                states: states.clone(),
+                symbols: trees.iter().map(|v| v.symbol.value.clone()).collect(),
+                input: input[position..].to_vec(),
+                action: action.clone(),
+            });
+
+            match action {
+                Some(Action::Shift(target)) => {
+                    trees.push(ParseTree::leaf(Symbol { kind: SymbolsKind::TERM, value: input[position].clone() }));
+                    states.push(target);
+                    position += 1;
+                }
+                Some(Action::Reduce(production)) => {
+                    let prod = &table.productions[production];
+                    let len = prod.expression.iter().filter(|v| v.kind != SymbolsKind::EPSILON).count();
+
+                    let mut children = trees.split_off(trees.len() - len);
+                    states.truncate(states.len() - len);
+
+                    if children.is_empty() {
+                        children.push(ParseTree::leaf(Symbol {
+                            kind: SymbolsKind::EPSILON,
+                            value: EPSILON_SYMBOL.to_string(),
+                        }));
+                    }
+
+                    let lhs = &prod.replaced_symbol.value;
+                    let target = table.goto[*states.last().unwrap()][lhs];
+
+                    trees.push(ParseTree { symbol: prod.replaced_symbol.clone(), children });
+                    states.push(target);
+                }
+                Some(Action::Accept) => {
+                    parse.result = Ok(trees.pop().unwrap());
+                    return Ok(parse);
+                }
+                None => {
+                    parse.result = Err(SyntaxError {
+                        position,
+                        found: input[position].clone(),
+                        expected: table.action[state].keys().cloned().collect(),
+                    });
+                    return Ok(parse);
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Display for LrParse<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut rows: Vec<Vec<String>> = vec![vec![
+            String::from("Stack"),
+            String::from("Input"),
+            String::from("Action"),
+        ]];
+
+        for step in self.steps.iter() {
+            // States interleaved with symbols: 0 E 1 + 6
+            let mut stack: Vec<String> = vec![step.states[0].to_string()];
+            for (symbol, state) in step.symbols.iter().zip(step.states[1..].iter()) {
+                stack.push(symbol.clone());
+                stack.push(state.to_string());
+            }
+
+            let action = match &step.action {
+                Some(Action::Shift(target)) => format!("shift {}", target),
+                Some(Action::Reduce(production)) => format!("reduce {}", self.table.productions[*production]),
+                Some(Action::Accept) => String::from("accept"),
+                None => String::from("error"),
+            };
+
+            rows.push(vec![stack.join(" "), step.input.join(" "), action]);
+        }
+
+        write_rows(f, &rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar::bnf;
+    use crate::grammar::lr::{build_table, LrMethod};
+
+    fn tokens(sentence: &str) -> Vec<String> {
+        sentence.split_whitespace().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn trace_shifts_and_reduces_to_accept() {
+        let g = bnf::parse("E -> E \"+\" T | T\nT -> id").unwrap();
+        let table = build_table(&g, LrMethod::Slr1);
+        let parse = LrParse::new(&table, &tokens("id + id")).unwrap();
+
+        // Action is the last column of the trace
+        let trace = parse.to_string();
+        let actions: Vec<&str> = trace.lines().skip(1).map(|v| v.rsplit(" | ").next().unwrap()).collect();
+        assert_eq!(
+            actions,
+            [
+                "shift 3",
+                "reduce T -> id",
+                "reduce E -> T",
+                "shift 4",
+                "shift 3",
+                "reduce T -> id",
+                "reduce E -> E + T",
+                "accept"
+            ]
+        );
+        assert_eq!(parse.steps[3].symbols, ["E"]);
+        assert_eq!(parse.steps[3].input, ["+", "id", "$"]);
+
+        let tree = parse.result.unwrap();
+        assert_eq!(tree.yield_terms(), tokens("id + id"));
+        assert_eq!(tree.children.len(), 3);
+    }
+
+    #[test]
+    fn syntax_error_reports_expected_tokens() {
+        let g = bnf::parse("E -> E \"+\" T | T\nT -> \"(\" E \")\" | id").unwrap();
+        let table = build_table(&g, LrMethod::Lalr1);
+
+        let e = LrParse::new(&table, &tokens("id + + id")).unwrap().result.unwrap_err();
+        assert_eq!(e.position, 2);
+        assert_eq!(e.found, "+");
+        assert_eq!(e.expected, ["(", "id"]);
+
+        let e = LrParse::new(&table, &tokens("( id")).unwrap().result.unwrap_err();
+        assert_eq!(e.found, "$");
+        assert_eq!(e.expected, [")", "+"]);
+    }
+
+    #[test]
+    fn table_with_conflicts_is_rejected() {
+        let g = bnf::parse("S -> L \"=\" R | R\nL -> \"*\" R | id\nR -> L").unwrap();
+        let table = build_table(&g, LrMethod::Slr1);
+
+        match LrParse::new(&table, &tokens("id = id")) {
+            Err(LrError::Conflicts(LrMethod::Slr1, conflicts)) => assert_eq!(conflicts.len(), 1),
+            v => panic!("Conflicts must be reported, got {:?}", v.map(|v| v.steps.len())),
+        }
+    }
+}
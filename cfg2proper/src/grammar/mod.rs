@@ -1,23 +1,40 @@
 pub mod transformations;
 pub mod parser;
+pub mod bnf;
+pub mod sets;
+pub mod ll1;
+pub mod codegen;
+pub mod emit;
+pub mod lint;
+pub mod cyk;
+pub mod earley;
+pub mod pipeline;
+pub mod lr;
+pub mod lr_parser;
+pub mod pda;
+pub mod regular;
+pub mod parse_tree;
+mod draw;
 
 use serde::{Deserialize, Serialize};
 
-use std::collections::{ HashSet, HashMap };
+use std::collections::{ BTreeSet, BTreeMap };
 use std::fmt::Display;
 
 pub const EPSILON_SYMBOL: char = '&';
+pub const END_MARKER_SYMBOL: char = '$';
 
 lazy_static! {
-    static ref U_CODEPOINTS: HashSet<char> = {
-        let mut h = HashSet::new();
+    static ref U_CODEPOINTS: BTreeSet<char> = {
+        let mut h = BTreeSet::new();
         h.insert('\u{030c}');
         h.insert('\u{0320}');
         h
     };
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum SymbolsKind {
     #[serde(rename = "term")]
     TERM,
@@ -28,28 +45,49 @@ pub enum SymbolsKind {
 }
 
 /// Symbol is represented here
-#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Symbol {
     pub kind: SymbolsKind,
     pub value: String,
 }
 
-/// Production is represented here
+/// Production is represented here. Productions are equal if their sides are
+/// equal, regardless of the origin
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Production {
     #[serde(rename = "lhs")]
     pub replaced_symbol: Symbol,
     #[serde(rename = "rhs")]
     pub expression: Vec<Symbol>,
+    #[serde(rename = "origin", default)]
+    pub origin: Origin,
+}
+
+/// Source production a production was derived from and the way it was derived
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Origin {
+    /// Line of the source production in the input file
+    #[serde(rename = "line", default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// Source production as it was read, None for productions introduced
+    /// by transformations
+    #[serde(rename = "source", default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Transformations which changed the production in order of application
+    #[serde(rename = "transformations", default, skip_serializing_if = "Vec::is_empty")]
+    pub transformations: Vec<String>,
+    /// Non-terminals replaced with their right sides
+    #[serde(rename = "substituted", default, skip_serializing_if = "Vec::is_empty")]
+    pub substituted: Vec<String>,
 }
 
 /// Grammar is represented here
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Grammar {
     #[serde(rename = "nonterms")]
-    pub non_terms: HashSet<String>,
+    pub non_terms: BTreeSet<String>,
     #[serde(rename = "terms")]
-    pub terms: HashSet<String>,
+    pub terms: BTreeSet<String>,
     #[serde(rename = "productions")]
     pub productions: Vec<Production>,
     #[serde(rename = "startsymbol")]
@@ -64,24 +102,33 @@ impl Grammar {
     /// * `non_terms` - Non-terminal symbols represented by &String
     ///
     /// * `terms` - Terminal symbols represented by &String
+    ///
+    /// * `prods` - Productions, only the first one of duplicate productions is kept
     pub fn new(
-        non_terms: HashSet<String>,
-        terms: HashSet<String>,
+        non_terms: BTreeSet<String>,
+        terms: BTreeSet<String>,
         prods: Vec<Production>,
         start: String,
     ) -> Grammar {
+        let mut productions: Vec<Production> = vec![];
+        for prod in prods.into_iter() {
+            if !productions.contains(&prod) {
+                productions.push(prod);
+            }
+        }
+
         Grammar {
             non_terms,
             terms,
-            productions: prods.clone(),
+            productions,
             start,
         }
     }
 
     #[allow(dead_code)]
     pub fn new_from_chars(
-        non_terms: HashSet<char>,
-        terms: HashSet<char>,
+        non_terms: BTreeSet<char>,
+        terms: BTreeSet<char>,
         prods: Vec<Production>,
         start: char,
     ) -> Grammar {
@@ -100,7 +147,7 @@ impl Production {
     /// # Arguments
     ///
     /// * `symbols` - Symbols which represent production rule. First element of Vec represents
-    ///     left part of rule(replaced symbol), others represent a right part of the rule
+    ///   left part of rule(replaced symbol), others represent a right part of the rule
     pub fn new(symbols: Vec<(SymbolsKind, String)>) -> Production {
         let symbols = symbols
             .into_iter()
@@ -113,6 +160,7 @@ impl Production {
         Production {
             replaced_symbol: (*first).clone(),
             expression: elements.to_vec(),
+            origin: Origin::default(),
         }
     }
 
@@ -129,10 +177,46 @@ impl Production {
         Production {
             replaced_symbol: (*first).clone(),
             expression: elements.to_vec(),
+            origin: Origin::default(),
         }
     }
 }
 
+impl PartialEq for Production {
+    fn eq(&self, other: &Self) -> bool {
+        self.replaced_symbol == other.replaced_symbol && self.expression == other.expression
+    }
+}
+
+impl Eq for Production {}
+
+impl Origin {
+    /// Returns the origin of a production derived by the transformation
+    ///
+    /// # Arguments
+    ///
+    /// * `transformation` - Name of the transformation, repeated application is recorded once
+    pub fn derived(&self, transformation: &str) -> Origin {
+        let mut origin = self.clone();
+        if origin.transformations.last().map(|v| v.as_str()) != Some(transformation) {
+            origin.transformations.push(transformation.to_string());
+        }
+
+        origin
+    }
+
+    /// Returns the origin of a production derived by the transformation
+    /// through replacing the non-terminal with one of its right sides
+    pub fn substituted(&self, transformation: &str, non_term: &str) -> Origin {
+        let mut origin = self.derived(transformation);
+        if !origin.substituted.iter().any(|v| v == non_term) {
+            origin.substituted.push(non_term.to_string());
+        }
+
+        origin
+    }
+}
+
 struct ProductionVec<'a>(&'a Vec<Production>);
 struct SymbolVec<'a>(&'a Vec<Symbol>);
 
@@ -153,7 +237,7 @@ impl<'a> Display for SymbolVec<'a> {
 
 impl<'a> Display for ProductionVec<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut lhs_prods_map: HashMap<String, Vec<Vec<Symbol>>> = HashMap::new();
+        let mut lhs_prods_map: BTreeMap<String, Vec<Vec<Symbol>>> = BTreeMap::new();
 
         for prod in self.0 {
             if let Some(v) = lhs_prods_map.get_mut(&prod.replaced_symbol.value) {
@@ -173,34 +257,64 @@ impl<'a> Display for ProductionVec<'a> {
                     write!(f, "{}", SymbolVec(rule))?;
                 }
             }
-            write!(f, "\n")?;
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Production {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} -> {}", self.replaced_symbol.value, SymbolVec(&self.expression))
+    }
+}
+
+impl Display for Origin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.line, &self.source) {
+            (Some(line), Some(source)) => write!(f, "line {}: {}", line, source)?,
+            (None, Some(source)) => write!(f, "{}", source)?,
+            _ => write!(f, "introduced")?,
+        }
+
+        if !self.transformations.is_empty() {
+            let separator = match self.source {
+                Some(_) => ", by",
+                None => " by",
+            };
+            write!(f, "{} {}", separator, self.transformations.join(", "))?;
+        }
+
+        if !self.substituted.is_empty() {
+            write!(f, ", substituted {}", self.substituted.join(", "))?;
         }
 
         Ok(())
     }
 }
 
-fn display_hashset(f: &mut std::fmt::Formatter<'_>, h: &HashSet<String>) -> std::fmt::Result {
+fn display_set(f: &mut std::fmt::Formatter<'_>, h: &BTreeSet<String>) -> std::fmt::Result {
     for s in h.iter() {
         write!(f, "{} ", s)?;
     }
-    write!(f, "\n")?;
+    writeln!(f)?;
 
     Ok(())
 }
 
 impl Display for Grammar {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "\n")?;
+        writeln!(f)?;
 
         write!(f, "Non-Terminals: ")?;
-        display_hashset(f, &self.non_terms)?;
+        display_set(f, &self.non_terms)?;
 
         write!(f, "Terminals: ")?;
-        display_hashset(f, &self.terms)?;
+        display_set(f, &self.terms)?;
 
-        write!(f, "Productions: \n")?;
-        write!(f, "{}\n", ProductionVec(&self.productions))?;
+        writeln!(f, "Productions: ")?;
+        writeln!(f, "{}", ProductionVec(&self.productions))?;
 
         write!(f, "Start: {}", self.start)?;
 
@@ -0,0 +1,78 @@
+use crate::grammar::{Symbol, SymbolsKind};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Parse tree node is represented here. Leaves are terminals or epsilon
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTree {
+    pub symbol: Symbol,
+    pub children: Vec<ParseTree>,
+}
+
+/// Output format of parse trees
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TreeFormat {
+    Text,
+    Dot,
+}
+
+impl FromStr for TreeFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(TreeFormat::Text),
+            "dot" => Ok(TreeFormat::Dot),
+            _ => Err(String::from("Unknown tree format ") + s + ", expected text or dot"),
+        }
+    }
+}
+
+impl ParseTree {
+    /// Returns a leaf node
+    pub fn leaf(symbol: Symbol) -> ParseTree {
+        ParseTree {
+            symbol,
+            children: vec![],
+        }
+    }
+
+    /// Returns terminals covered by the tree from left to right
+    pub fn yield_terms(&self) -> Vec<String> {
+        if self.children.is_empty() {
+            return match self.symbol.kind {
+                SymbolsKind::TERM => vec![self.symbol.value.clone()],
+                _ => vec![],
+            };
+        }
+
+        self.children.iter().flat_map(|child| child.yield_terms()).collect()
+    }
+
+    /// Returns the tree rendered in the given format
+    pub fn render(&self, format: TreeFormat) -> String {
+        match format {
+            TreeFormat::Text => self.to_string(),
+            TreeFormat::Dot => {
+                let mut output: Vec<u8> = vec![];
+                dot::render(&self.graph(), &mut output).unwrap();
+                String::from_utf8(output).unwrap()
+            }
+        }
+    }
+
+    fn fmt_indented(&self, f: &mut std::fmt::Formatter<'_>, depth: usize) -> std::fmt::Result {
+        writeln!(f, "{}{}", "  ".repeat(depth), self.symbol.value)?;
+        for child in self.children.iter() {
+            child.fmt_indented(f, depth + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for ParseTree {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
@@ -1,7 +1,12 @@
+use crate::grammar::bnf;
 use crate::grammar::{Grammar, Production, Symbol, SymbolsKind, EPSILON_SYMBOL};
+use itertools::Itertools;
+use serde::Serialize;
 use std::error::Error;
 use std::{ fs, fmt };
-use std::collections::HashSet;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::iter;
 use std::io::prelude::*;
 
 #[derive(Debug)]
@@ -52,12 +57,26 @@ impl Error for ParseError {}
 
 pub fn parse_from_file(filename: &String) -> Result<Grammar, Box<dyn Error>> {
     let content = fs::read_to_string(filename)?;
-    let g = parse(&content)?;
+
+    // Format is chosen by the file extension, count-prefixed text format is the default
+    let mut g = match Path::new(filename).extension().and_then(|v| v.to_str()) {
+        Some("bnf") | Some("ebnf") => bnf::parse(&content)?,
+        Some("json") => parse_json(&content)?,
+        _ => parse(&content)?,
+    };
+
+    // Productions read from the file are their own sources, JSON input may
+    // already carry origins of a previous run
+    for prod in g.productions.iter_mut() {
+        if prod.origin.source.is_none() && prod.origin.transformations.is_empty() {
+            prod.origin.source = Some(prod.to_string());
+        }
+    }
 
     Ok(g)
 }
 
-fn parse(content: &String) -> Result<Grammar, Box<dyn Error>> {
+fn parse(content: &str) -> Result<Grammar, Box<dyn Error>> {
     let mut rows = content.split('\n');
 
     let non_terms_set = parse_non_terms(&mut rows)?;
@@ -71,7 +90,49 @@ fn parse(content: &String) -> Result<Grammar, Box<dyn Error>> {
     Ok(Grammar::new(non_terms_set, terms_set, productions, start))
 }
 
-fn parse_non_terms<'a, I>(it: &mut I) -> Result<HashSet<String>, Box<dyn Error>>
+// Reads grammar in the format written by write_json_to_file, unknown fields
+// (e.g. FIRST and FOLLOW sets) are ignored
+fn parse_json(content: &str) -> Result<Grammar, Box<dyn Error>> {
+    let g: Grammar = serde_json::from_str(content)?;
+
+    validate(&g)?;
+
+    // Rebuilding removes duplicate productions as the other formats do
+    Ok(Grammar::new(g.non_terms, g.terms, g.productions, g.start))
+}
+
+// Checks the same invariants the text format parser does
+fn validate(g: &Grammar) -> Result<(), Box<dyn Error>> {
+    for prod in g.productions.iter() {
+        if prod.replaced_symbol.kind != SymbolsKind::NONTERM {
+            return Err(ParseError::InvalidProductionStart.into());
+        }
+
+        if prod.expression.is_empty() {
+            return Err(ParseError::InvalidProduction.into());
+        }
+
+        for symbol in iter::once(&prod.replaced_symbol).chain(prod.expression.iter()) {
+            let declared = match symbol.kind {
+                SymbolsKind::NONTERM => g.non_terms.contains(&symbol.value),
+                SymbolsKind::TERM => g.terms.contains(&symbol.value),
+                SymbolsKind::EPSILON => symbol.value == EPSILON_SYMBOL.to_string(),
+            };
+
+            if !declared {
+                return Err(ParseError::FailedConvertIntoSymbol.into());
+            }
+        }
+    }
+
+    if !g.non_terms.contains(&g.start) {
+        return Err(ParseError::InvalidStartSymbol.into());
+    }
+
+    Ok(())
+}
+
+fn parse_non_terms<'a, I>(it: &mut I) -> Result<BTreeSet<String>, Box<dyn Error>>
     where I: Iterator<Item = &'a str>,
 {
     let non_term_n = it.next().ok_or(ParseError::NonTermsNNotFound)?;
@@ -79,7 +140,9 @@ fn parse_non_terms<'a, I>(it: &mut I) -> Result<HashSet<String>, Box<dyn Error>>
 
     let non_terms_raw = it.next().ok_or(ParseError::NonTermsNotFound)?;
 
-    let non_terms_set: HashSet<String> = non_terms_raw.split(' ')
+    // Empty line stands for an empty set
+    let non_terms_set: BTreeSet<String> = non_terms_raw.split(' ')
+        .filter(|v| !v.is_empty())
         .map(ToString::to_string)
         .collect();
 
@@ -90,7 +153,7 @@ fn parse_non_terms<'a, I>(it: &mut I) -> Result<HashSet<String>, Box<dyn Error>>
     Ok(non_terms_set)
 }
 
-fn parse_terms<'a, I>(it: &mut I) -> Result<HashSet<String>, Box<dyn Error>>
+fn parse_terms<'a, I>(it: &mut I) -> Result<BTreeSet<String>, Box<dyn Error>>
     where I: Iterator<Item = &'a str>,
 {
     let term_n = it.next().ok_or(ParseError::TermsNNotFound)?;
@@ -98,7 +161,9 @@ fn parse_terms<'a, I>(it: &mut I) -> Result<HashSet<String>, Box<dyn Error>>
 
     let terms_raw = it.next().ok_or(ParseError::TermsNotFound)?;
 
-    let terms_set: HashSet<String> = terms_raw.split(' ')
+    // Empty line stands for an empty set
+    let terms_set: BTreeSet<String> = terms_raw.split(' ')
+        .filter(|v| !v.is_empty())
         .map(ToString::to_string)
         .collect();
 
@@ -109,7 +174,7 @@ fn parse_terms<'a, I>(it: &mut I) -> Result<HashSet<String>, Box<dyn Error>>
     Ok(terms_set)
 }
 
-fn parse_productions<'a, I>(it: &mut I, non_terms: &HashSet<String>, terms: &HashSet<String>) -> Result<Vec<Production>, Box<dyn Error>>
+fn parse_productions<'a, I>(it: &mut I, non_terms: &BTreeSet<String>, terms: &BTreeSet<String>) -> Result<Vec<Production>, Box<dyn Error>>
     where I: Iterator<Item = &'a str>,
 {
     let production_n = it.next().ok_or(ParseError::ProductionsNNotFound)?;
@@ -117,7 +182,8 @@ fn parse_productions<'a, I>(it: &mut I, non_terms: &HashSet<String>, terms: &Has
 
     let mut productions: Vec<Production> = vec![];
 
-    for _i in 0..production_n {
+    // Productions follow non-terminals, terminals and productions count rows
+    for i in 0..production_n {
         let production = it.next().ok_or(ParseError::ExpectedProductionNotFound)?;
         let production_symbols: Vec<Symbol> = production.split(' ')
             .map(|s| convert_string_to_symbol(&s.to_string(), non_terms, terms))
@@ -131,15 +197,17 @@ fn parse_productions<'a, I>(it: &mut I, non_terms: &HashSet<String>, terms: &Has
             return Err(ParseError::InvalidProductionStart.into());
         }
 
-        productions.push(
-            Production::new(production_symbols.into_iter().map(|sym| (sym.kind, sym.value)).collect::<Vec<_>>())
-        );
+        let mut production =
+            Production::new(production_symbols.into_iter().map(|sym| (sym.kind, sym.value)).collect::<Vec<_>>());
+        production.origin.line = Some(i + 6);
+
+        productions.push(production);
     }
 
     Ok(productions)
 }
 
-fn parse_start<'a, I>(it: &mut I, non_terms: &HashSet<String>) -> Result<String, Box<dyn Error>>
+fn parse_start<'a, I>(it: &mut I, non_terms: &BTreeSet<String>) -> Result<String, Box<dyn Error>>
     where I: Iterator<Item = &'a str>,
 {
     let start = it.next().ok_or(ParseError::StartSymbolNotFound)?;
@@ -151,7 +219,7 @@ fn parse_start<'a, I>(it: &mut I, non_terms: &HashSet<String>) -> Result<String,
     Ok(start.to_string())
 }
 
-fn convert_string_to_symbol(s: &String, non_terms: &HashSet<String>, terms: &HashSet<String>) -> Result<Symbol, Box<dyn Error>> {
+fn convert_string_to_symbol(s: &String, non_terms: &BTreeSet<String>, terms: &BTreeSet<String>) -> Result<Symbol, Box<dyn Error>> {
     if *s == EPSILON_SYMBOL.to_string() {
         return Ok(
             Symbol {
@@ -182,10 +250,84 @@ fn convert_string_to_symbol(s: &String, non_terms: &HashSet<String>, terms: &Has
     Err(ParseError::FailedConvertIntoSymbol.into())
 }
 
-pub fn write_json_to_file(g: &Grammar, filename: &str) -> Result<(), Box<dyn Error>> {
+pub fn write_json_to_file<T: Serialize>(g: &T, filename: &str) -> Result<(), Box<dyn Error>> {
     let mut file = fs::File::create(filename)?;
     let j = serde_json::to_string(g)?;
     file.write_all(j.as_bytes())?;
 
     Ok(())
 }
+
+/// Output format of the resulting grammar
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OutputFormat {
+    Json,
+    Text,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(OutputFormat::Json),
+            "text" => Ok(OutputFormat::Text),
+            _ => Err(String::from("Unknown output format ") + s + ", expected json or text"),
+        }
+    }
+}
+
+// Writes grammar in the count-prefixed text format read by parse_from_file
+pub fn write_text_to_file(g: &Grammar, filename: &str) -> Result<(), Box<dyn Error>> {
+    let mut lines: Vec<String> = vec![
+        g.non_terms.len().to_string(),
+        g.non_terms.iter().join(" "),
+        g.terms.len().to_string(),
+        g.terms.iter().join(" "),
+        g.productions.len().to_string(),
+    ];
+
+    for prod in g.productions.iter() {
+        lines.push(iter::once(&prod.replaced_symbol).chain(prod.expression.iter()).map(|v| &v.value).join(" "));
+    }
+
+    lines.push(g.start.clone());
+
+    let mut file = fs::File::create(filename)?;
+    file.write_all((lines.join("\n") + "\n").as_bytes())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_duplicate_productions_are_removed() {
+        let prod = r#"{"lhs": {"kind": "nonterm", "value": "S"}, "rhs": [{"kind": "term", "value": "a"}]}"#;
+        let content = format!(
+            r#"{{"nonterms": ["S"], "terms": ["a"], "productions": [{}, {}], "startsymbol": "S"}}"#,
+            prod, prod
+        );
+
+        let g = parse_json(&content).unwrap();
+
+        assert_eq!(g.productions.len(), 1);
+    }
+
+    #[test]
+    fn text_without_terminals_round_trips() {
+        let g = bnf::parse("S -> A | &\nA -> S").unwrap();
+        let filename = std::env::temp_dir().join("cfg2proper_round_trip.txt").to_string_lossy().to_string();
+
+        write_text_to_file(&g, &filename).unwrap();
+        let read = parse_from_file(&filename).unwrap();
+        fs::remove_file(&filename).unwrap();
+
+        assert!(read.terms.is_empty());
+        assert_eq!(read.non_terms, g.non_terms);
+        assert_eq!(read.productions, g.productions);
+        assert_eq!(read.start, g.start);
+    }
+}
@@ -0,0 +1,581 @@
+use crate::grammar::lr::{write_rows, Lr0Automaton};
+use crate::grammar::{Grammar, Symbol, SymbolsKind, END_MARKER_SYMBOL, EPSILON_SYMBOL};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt::Display;
+use std::fs::File;
+use std::str::FromStr;
+
+/// Construction of a PDA equivalent to the grammar
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PdaConstruction {
+    /// Single state, expands non-terminal on top of the stack with one of its
+    /// right sides and matches terminal on top with the input, accepts by
+    /// empty stack
+    TopDown,
+    /// Shifts terminals onto the stack and reduces right sides on top of it
+    /// to their left side, accepts by final state when only the start symbol
+    /// is left above the bottom marker
+    BottomUp,
+}
+
+impl FromStr for PdaConstruction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "top-down" => Ok(PdaConstruction::TopDown),
+            "bottom-up" => Ok(PdaConstruction::BottomUp),
+            _ => Err(String::from("Unknown PDA construction ") + s + ", expected one of top-down, bottom-up"),
+        }
+    }
+}
+
+impl Display for PdaConstruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PdaConstruction::TopDown => write!(f, "top-down"),
+            PdaConstruction::BottomUp => write!(f, "bottom-up"),
+        }
+    }
+}
+
+/// Move of PDA: in state `from` reading `input` (None is epsilon move) with
+/// `pop` on top of the stack go to state `to` replacing it with `push`.
+/// Stack strings are written top first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PdaTransition {
+    pub from: usize,
+    pub input: Option<String>,
+    pub pop: Vec<String>,
+    pub to: usize,
+    pub push: Vec<String>,
+}
+
+/// Nondeterministic pushdown automaton
+#[derive(Debug)]
+pub struct Pda {
+    pub construction: PdaConstruction,
+    pub states: Vec<String>,
+    pub start_state: usize,
+    pub start_stack: String,
+    /// Final states, empty if the automaton accepts by empty stack
+    pub accepting: BTreeSet<usize>,
+    pub transitions: Vec<PdaTransition>,
+    // Minimal length of terminal string every stack symbol stands for,
+    // prunes configurations which can't lead to acceptance
+    yields: BTreeMap<String, usize>,
+    // GOTO function of LR(0) automaton of the grammar for bottom-up PDA,
+    // every stack of its accepting run is a viable prefix
+    viable_prefixes: Vec<BTreeMap<String, usize>>,
+    // Longest right side times number of non-terminals, bounds stack height
+    // of runs over minimal parse trees
+    height_factor: usize,
+}
+
+/// Instantaneous description of PDA, stack top is the last element
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PdaConfiguration {
+    pub state: usize,
+    pub position: usize,
+    pub stack: Vec<String>,
+}
+
+/// Configuration of an accepting run and the transition taken in it,
+/// None for the last one
+#[derive(Debug)]
+pub struct PdaStep {
+    pub configuration: PdaConfiguration,
+    pub transition: Option<usize>,
+}
+
+/// Limits of the search for an accepting run
+#[derive(Debug, Copy, Clone)]
+pub struct SearchBounds {
+    pub max_configurations: usize,
+    pub max_stack: usize,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PdaVerdict {
+    Accepted,
+    Rejected,
+    /// No accepting run was found, but the bounds cut off part of the search
+    Undecided,
+}
+
+/// Result of the search for an accepting run of PDA over a sentence
+#[derive(Debug)]
+pub struct PdaRun<'a> {
+    pub verdict: PdaVerdict,
+    /// Accepting run, empty unless the sentence is accepted
+    pub steps: Vec<PdaStep>,
+    pub explored: usize,
+    tokens: Vec<String>,
+    pda: &'a Pda,
+}
+
+impl Default for SearchBounds {
+    fn default() -> Self {
+        SearchBounds { max_configurations: 100_000, max_stack: 64 }
+    }
+}
+
+impl Pda {
+    /// Returns PDA accepting the language of the grammar
+    ///
+    /// # Arguments
+    ///
+    /// * `g` - Grammar to convert
+    ///
+    /// * `construction` - Whether PDA simulates leftmost derivations top-down
+    ///   or rightmost derivations bottom-up
+    pub fn new(g: &Grammar, construction: PdaConstruction) -> Pda {
+        let body = |expression: &[Symbol]| -> Vec<String> {
+            expression
+                .iter()
+                .filter(|v| v.kind != SymbolsKind::EPSILON)
+                .map(|v| v.value.clone())
+                .collect()
+        };
+
+        let mut yields = get_min_yields(g);
+        let mut transitions: Vec<PdaTransition> = vec![];
+
+        let longest = g.productions.iter().map(|prod| body(&prod.expression).len()).max().unwrap_or(0);
+        let height_factor = longest.max(1) * g.non_terms.len();
+
+        match construction {
+            PdaConstruction::TopDown => {
+                for prod in g.productions.iter() {
+                    transitions.push(PdaTransition {
+                        from: 0,
+                        input: None,
+                        pop: vec![prod.replaced_symbol.value.clone()],
+                        to: 0,
+                        push: body(&prod.expression),
+                    });
+                }
+
+                for term in g.terms.iter() {
+                    transitions.push(PdaTransition {
+                        from: 0,
+                        input: Some(term.clone()),
+                        pop: vec![term.clone()],
+                        to: 0,
+                        push: vec![],
+                    });
+                }
+
+                Pda {
+                    construction,
+                    states: vec![String::from("q")],
+                    start_state: 0,
+                    start_stack: g.start.clone(),
+                    accepting: BTreeSet::new(),
+                    transitions,
+                    yields,
+                    viable_prefixes: vec![],
+                    height_factor,
+                }
+            }
+            PdaConstruction::BottomUp => {
+                let bottom = END_MARKER_SYMBOL.to_string();
+
+                for term in g.terms.iter() {
+                    transitions.push(PdaTransition {
+                        from: 0,
+                        input: Some(term.clone()),
+                        pop: vec![],
+                        to: 0,
+                        push: vec![term.clone()],
+                    });
+                }
+
+                // Right side is on the stack with its last symbol on top
+                for prod in g.productions.iter() {
+                    let mut pop = body(&prod.expression);
+                    pop.reverse();
+
+                    transitions.push(PdaTransition {
+                        from: 0,
+                        input: None,
+                        pop,
+                        to: 0,
+                        push: vec![prod.replaced_symbol.value.clone()],
+                    });
+                }
+
+                transitions.push(PdaTransition {
+                    from: 0,
+                    input: None,
+                    pop: vec![g.start.clone(), bottom.clone()],
+                    to: 1,
+                    push: vec![],
+                });
+
+                yields.insert(bottom.clone(), 0);
+
+                Pda {
+                    construction,
+                    states: vec![String::from("p"), String::from("f")],
+                    start_state: 0,
+                    start_stack: bottom,
+                    accepting: vec![1].into_iter().collect(),
+                    transitions,
+                    yields,
+                    viable_prefixes: Lr0Automaton::new(g).goto,
+                    height_factor,
+                }
+            }
+        }
+    }
+
+    /// Returns the result of breadth-first search for an accepting run over
+    /// the sentence, the run found is one of the shortest
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - Terminals of the sentence
+    ///
+    /// * `bounds` - Maximum number of configurations to explore and maximum
+    ///   stack height
+    pub fn run(&self, tokens: &[String], bounds: SearchBounds) -> PdaRun<'_> {
+        let start = PdaConfiguration {
+            state: self.start_state,
+            position: 0,
+            stack: vec![self.start_stack.clone()],
+        };
+
+        // Configurations with the configuration and transition they were reached from
+        let mut configurations: Vec<(PdaConfiguration, Option<(usize, usize)>)> = vec![(start.clone(), None)];
+        let mut visited: BTreeSet<PdaConfiguration> = BTreeSet::new();
+        visited.insert(start);
+
+        let mut queue: VecDeque<usize> = VecDeque::new();
+        queue.push_back(0);
+
+        // Minimal parse tree doesn't repeat a non-terminal with the same span on
+        // a path, so at most |N| * (n + 1) of its nodes are open at once, each
+        // with fewer children on the stack than its right side has. Higher
+        // stacks aren't needed to find an accepting run if there is one
+        let max_height = self.height_factor * (tokens.len() + 1) + 2;
+
+        let mut run = PdaRun {
+            verdict: PdaVerdict::Rejected,
+            steps: vec![],
+            explored: 0,
+            tokens: tokens.to_vec(),
+            pda: self,
+        };
+
+        while let Some(current) = queue.pop_front() {
+            run.explored += 1;
+
+            if self.accepts(&configurations[current].0, tokens.len()) {
+                run.verdict = PdaVerdict::Accepted;
+                run.steps = self.unwind(&configurations, current);
+                return run;
+            }
+
+            if run.explored >= bounds.max_configurations {
+                run.verdict = PdaVerdict::Undecided;
+                return run;
+            }
+
+            for (i, transition) in self.transitions.iter().enumerate() {
+                let next = match self.apply(&configurations[current].0, transition, tokens) {
+                    Some(v) => v,
+                    None => continue,
+                };
+
+                if next.stack.len() > max_height || !self.may_accept(&next, tokens.len()) || visited.contains(&next) {
+                    continue;
+                }
+
+                if next.stack.len() > bounds.max_stack {
+                    run.verdict = PdaVerdict::Undecided;
+                    continue;
+                }
+
+                visited.insert(next.clone());
+                configurations.push((next, Some((current, i))));
+                queue.push_back(configurations.len() - 1);
+            }
+        }
+
+        run
+    }
+
+    /// Renders the automaton in dot format to the file
+    pub fn render_to(&self, output: &str) {
+        let mut f = File::create(output).unwrap();
+        dot::render(self, &mut f).unwrap()
+    }
+
+    /// Returns the transition as `q, a, X Y / p, Z`
+    pub fn transition_to_string(&self, transition: &PdaTransition) -> String {
+        format!(
+            "{}, {}, {} / {}, {}",
+            self.states[transition.from],
+            input_to_string(transition),
+            stack_to_string(transition.pop.iter()),
+            self.states[transition.to],
+            stack_to_string(transition.push.iter())
+        )
+    }
+
+    /// Returns the transition without states as `a, X Y / Z`
+    pub fn label_to_string(&self, transition: &PdaTransition) -> String {
+        format!(
+            "{}, {} / {}",
+            input_to_string(transition),
+            stack_to_string(transition.pop.iter()),
+            stack_to_string(transition.push.iter())
+        )
+    }
+
+    fn accepts(&self, configuration: &PdaConfiguration, len: usize) -> bool {
+        configuration.position == len
+            && match self.accepting.is_empty() {
+                true => configuration.stack.is_empty(),
+                _ => self.accepting.contains(&configuration.state),
+            }
+    }
+
+    fn apply(&self, configuration: &PdaConfiguration, transition: &PdaTransition, tokens: &[String]) -> Option<PdaConfiguration> {
+        if transition.from != configuration.state {
+            return None;
+        }
+
+        let mut position = configuration.position;
+        if let Some(input) = &transition.input {
+            if tokens.get(position) != Some(input) {
+                return None;
+            }
+            position += 1;
+        }
+
+        let stack = &configuration.stack;
+        if transition.pop.len() > stack.len() || !transition.pop.iter().zip(stack.iter().rev()).all(|(a, b)| a == b) {
+            return None;
+        }
+
+        let mut stack = stack[..stack.len() - transition.pop.len()].to_vec();
+        stack.extend(transition.push.iter().rev().cloned());
+
+        Some(PdaConfiguration { state: transition.to, position, stack })
+    }
+
+    // Top-down stack has to derive the rest of the input, bottom-up stack
+    // is derived from the input read and is a viable prefix. The latter stops
+    // runs stacking epsilon reductions of nullable symbols which no right
+    // side can take, so rejection doesn't exhaust the bounds
+    fn may_accept(&self, configuration: &PdaConfiguration, len: usize) -> bool {
+        let stack_yield = configuration
+            .stack
+            .iter()
+            .fold(0usize, |acc, v| acc.saturating_add(*self.yields.get(v).unwrap_or(&usize::MAX)));
+
+        match self.construction {
+            PdaConstruction::TopDown => stack_yield <= len - configuration.position,
+            PdaConstruction::BottomUp => {
+                stack_yield <= configuration.position && self.is_viable_prefix(configuration.stack.iter().skip(1))
+            }
+        }
+    }
+
+    // Follows the stack above the bottom marker through LR(0) automaton
+    fn is_viable_prefix<'a, I>(&self, stack: I) -> bool
+    where
+        I: Iterator<Item = &'a String>,
+    {
+        let mut state = 0;
+        for symbol in stack {
+            state = match self.viable_prefixes[state].get(symbol) {
+                Some(v) => *v,
+                None => return false,
+            };
+        }
+
+        true
+    }
+
+    fn unwind(&self, configurations: &[(PdaConfiguration, Option<(usize, usize)>)], last: usize) -> Vec<PdaStep> {
+        let mut steps = vec![PdaStep { configuration: configurations[last].0.clone(), transition: None }];
+        let mut current = last;
+
+        while let Some((parent, transition)) = configurations[current].1 {
+            steps.push(PdaStep { configuration: configurations[parent].0.clone(), transition: Some(transition) });
+            current = parent;
+        }
+        steps.reverse();
+
+        steps
+    }
+}
+
+// Terminals yield themselves, non-terminals the shortest terminal string
+// they derive, unproductive ones are left out
+fn get_min_yields(g: &Grammar) -> BTreeMap<String, usize> {
+    let mut yields: BTreeMap<String, usize> = g.terms.iter().map(|v| (v.clone(), 1)).collect();
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+
+        for prod in g.productions.iter() {
+            let length = prod.expression.iter().try_fold(0usize, |acc, v| match v.kind {
+                SymbolsKind::EPSILON => Some(acc),
+                _ => yields.get(&v.value).map(|length| acc + length),
+            });
+
+            if let Some(length) = length {
+                let current = yields.entry(prod.replaced_symbol.value.clone()).or_insert(usize::MAX);
+                if length < *current {
+                    *current = length;
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    yields
+}
+
+fn input_to_string(transition: &PdaTransition) -> String {
+    transition.input.clone().unwrap_or_else(|| EPSILON_SYMBOL.to_string())
+}
+
+fn stack_to_string<'a>(stack: impl Iterator<Item = &'a String>) -> String {
+    let symbols: Vec<&str> = stack.map(|v| v.as_str()).collect();
+
+    match symbols.is_empty() {
+        true => EPSILON_SYMBOL.to_string(),
+        _ => symbols.join(" "),
+    }
+}
+
+impl Display for Pda {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let acceptance = match self.accepting.is_empty() {
+            true => String::from("empty stack"),
+            _ => {
+                let states: Vec<&str> = self.accepting.iter().map(|v| self.states[*v].as_str()).collect();
+                format!("final state {}", states.join(", "))
+            }
+        };
+
+        writeln!(f, "States: {}", self.states.join(", "))?;
+        writeln!(f, "Start state: {}", self.states[self.start_state])?;
+        writeln!(f, "Start stack symbol: {}", self.start_stack)?;
+        writeln!(f, "Accepts by {}", acceptance)?;
+        writeln!(f, "Transitions (stack top first):")?;
+
+        let mut rows: Vec<Vec<String>> = vec![vec![
+            String::from("#"),
+            String::from("From"),
+            String::from("Input"),
+            String::from("Pop"),
+            String::from("To"),
+            String::from("Push"),
+        ]];
+
+        for (i, transition) in self.transitions.iter().enumerate() {
+            rows.push(vec![
+                i.to_string(),
+                self.states[transition.from].clone(),
+                input_to_string(transition),
+                stack_to_string(transition.pop.iter()),
+                self.states[transition.to].clone(),
+                stack_to_string(transition.push.iter()),
+            ]);
+        }
+
+        write_rows(f, &rows)
+    }
+}
+
+impl Display for PdaVerdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PdaVerdict::Accepted => write!(f, "accepted"),
+            PdaVerdict::Rejected => write!(f, "rejected"),
+            PdaVerdict::Undecided => write!(f, "undecided, search bounds are reached"),
+        }
+    }
+}
+
+impl<'a> Display for PdaRun<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut rows: Vec<Vec<String>> = vec![vec![
+            String::from("State"),
+            String::from("Input"),
+            String::from("Stack"),
+            String::from("Transition"),
+        ]];
+
+        for step in self.steps.iter() {
+            let configuration = &step.configuration;
+            let input = match configuration.position < self.tokens.len() {
+                true => self.tokens[configuration.position..].join(" "),
+                _ => EPSILON_SYMBOL.to_string(),
+            };
+            let transition = match step.transition {
+                Some(i) => format!("{}: {}", i, self.pda.transition_to_string(&self.pda.transitions[i])),
+                None => String::from("accept"),
+            };
+
+            rows.push(vec![
+                self.pda.states[configuration.state].clone(),
+                input,
+                stack_to_string(configuration.stack.iter().rev()),
+                transition,
+            ]);
+        }
+
+        write_rows(f, &rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar::bnf;
+
+    fn verdict(content: &str, construction: PdaConstruction, sentence: &str) -> PdaVerdict {
+        let g = bnf::parse(content).unwrap();
+        let tokens: Vec<String> = sentence.split_whitespace().map(|v| v.to_string()).collect();
+
+        Pda::new(&g, construction).run(&tokens, SearchBounds::default()).verdict
+    }
+
+    #[test]
+    fn rejection_on_nullable_grammar_terminates() {
+        let content = "S -> A B\nA -> a A | &\nB -> b | &";
+
+        for construction in [PdaConstruction::TopDown, PdaConstruction::BottomUp].iter() {
+            assert_eq!(verdict(content, *construction, "a b b"), PdaVerdict::Rejected);
+            assert_eq!(verdict(content, *construction, "b a"), PdaVerdict::Rejected);
+            assert_eq!(verdict(content, *construction, "a a b"), PdaVerdict::Accepted);
+            assert_eq!(verdict(content, *construction, ""), PdaVerdict::Accepted);
+        }
+    }
+
+    #[test]
+    fn bottom_up_keeps_needed_epsilon_reductions() {
+        let content = "S -> A A A S b | c\nA -> &";
+
+        assert_eq!(verdict(content, PdaConstruction::BottomUp, "c b b"), PdaVerdict::Accepted);
+        assert_eq!(verdict(content, PdaConstruction::BottomUp, "c b c"), PdaVerdict::Rejected);
+    }
+
+    #[test]
+    fn rejection_on_cyclic_grammar_terminates() {
+        let content = "S -> a S b | S S | &";
+
+        for construction in [PdaConstruction::TopDown, PdaConstruction::BottomUp].iter() {
+            assert_eq!(verdict(content, *construction, "a b b a"), PdaVerdict::Rejected);
+            assert_eq!(verdict(content, *construction, "a b a a b b"), PdaVerdict::Accepted);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::grammar::transformations::{self, TransformationError};
+use crate::grammar::Grammar;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Pipeline used when no stages are given: conversion to proper grammar
+/// without left recursion
+pub const DEFAULT_PIPELINE: &str = "e-free,useless,unit,left-rec";
+
+/// Grammar transformation which can be used as a pipeline stage
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Stage {
+    EFree,
+    Useless,
+    Unreachable,
+    Unit,
+    LeftRec,
+    LeftFactor,
+    Cnf,
+    Gnf,
+}
+
+impl FromStr for Stage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "e-free" => Ok(Stage::EFree),
+            "useless" => Ok(Stage::Useless),
+            "unreachable" => Ok(Stage::Unreachable),
+            "unit" => Ok(Stage::Unit),
+            "left-rec" => Ok(Stage::LeftRec),
+            "left-factor" => Ok(Stage::LeftFactor),
+            "cnf" => Ok(Stage::Cnf),
+            "gnf" => Ok(Stage::Gnf),
+            _ => Err(String::from("Unknown pipeline stage ") + s
+                + ", expected one of e-free, useless, unreachable, unit, left-rec, left-factor, cnf, gnf"),
+        }
+    }
+}
+
+impl Display for Stage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Stage::EFree => "e-free",
+            Stage::Useless => "useless",
+            Stage::Unreachable => "unreachable",
+            Stage::Unit => "unit",
+            Stage::LeftRec => "left-rec",
+            Stage::LeftFactor => "left-factor",
+            Stage::Cnf => "cnf",
+            Stage::Gnf => "gnf",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl Stage {
+    /// Returns the grammar transformed by the stage or an error if the result
+    /// breaks invariants of the stage
+    ///
+    /// # Arguments
+    ///
+    /// * `g` - Grammar to transform
+    ///
+    /// * `lr_order` - Order of non-terminals for left recursion elimination
+    pub fn apply(&self, g: &Grammar, lr_order: &[String]) -> Result<Grammar, TransformationError> {
+        let result = match self {
+            Stage::EFree => transformations::to_e_free(g),
+            Stage::Useless => transformations::remove_useless_symbols(g),
+            Stage::Unreachable => transformations::remove_unreachable(g),
+            Stage::Unit => transformations::remove_unit_productions(g),
+            Stage::LeftRec => transformations::eliminate_indirect_lr(g, lr_order),
+            Stage::LeftFactor => transformations::left_factor(g),
+            Stage::Cnf => {
+                let cnf = transformations::to_cnf(g);
+                if !transformations::is_cnf(&cnf) {
+                    return Err(TransformationError::NotNormalForm("Chomsky"));
+                }
+                cnf
+            }
+            Stage::Gnf => transformations::to_gnf(g)?,
+        };
+
+        Ok(result)
+    }
+}
+
+/// Returns the grammar transformed by every stage in order or the error
+/// of the first failed stage
+///
+/// # Arguments
+///
+/// * `g` - Grammar to transform
+///
+/// * `stages` - Transformations to apply
+///
+/// * `lr_order` - Order of non-terminals for left recursion elimination
+///
+/// * `on_stage` - Called with every stage and its result
+pub fn run<F>(
+    g: &Grammar,
+    stages: &[Stage],
+    lr_order: &[String],
+    mut on_stage: F,
+) -> Result<Grammar, TransformationError>
+where
+    F: FnMut(Stage, &Grammar),
+{
+    let mut result = Grammar::new(g.non_terms.clone(), g.terms.clone(), g.productions.clone(), g.start.clone());
+
+    for stage in stages.iter() {
+        result = stage.apply(&result, lr_order)?;
+        on_stage(*stage, &result);
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,679 @@
+use crate::grammar::codegen::unique_name;
+use crate::grammar::lr::write_rows;
+use crate::grammar::{Grammar, Origin, Production, Symbol, SymbolsKind, EPSILON_SYMBOL};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt::Display;
+use std::fs::{self, File};
+
+// Concatenation and end marker operators of regex2fsm, they have to be escaped
+const RESERVED_SYMBOLS: &str = ".#";
+
+/// Shape of regular grammar productions
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Linearity {
+    /// A -> w B or A -> w, w is a string of terminals
+    RightLinear,
+    /// A -> B w or A -> w, w is a string of terminals
+    LeftLinear,
+}
+
+/// Finite automaton with epsilon moves, fields follow Nfa of regex2fsm.
+/// Display writes it in the automaton file format of regex2fsm
+#[derive(Debug)]
+pub struct Nfa {
+    pub alphabet: Vec<String>,
+    pub states: Vec<String>,
+    pub trans: Vec<Vec<Vec<usize>>>,
+    pub epsilon_trans: Vec<Vec<usize>>,
+    pub start: usize,
+    pub is_terminal: BTreeSet<usize>,
+}
+
+/// Deterministic finite automaton serialized with the fields of Dfa of
+/// regex2fsm. State 0 is the start state, every state is a set of NFA states
+#[derive(Debug, Serialize)]
+pub struct Dfa {
+    pub alphabet: Vec<String>,
+    pub states: Vec<Vec<usize>>,
+    pub trans: Vec<Vec<Option<usize>>>,
+    pub is_terminal: BTreeSet<usize>,
+}
+
+/// Regex which doesn't follow regex2fsm syntax
+#[derive(Debug)]
+pub struct RegexError {
+    pub position: usize,
+    pub message: String,
+}
+
+#[derive(Debug)]
+enum Regex {
+    Empty,
+    Char(String),
+    Concat(Vec<Regex>),
+    Alt(Vec<Regex>),
+    Star(Box<Regex>),
+}
+
+struct RegexParser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Display for Linearity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Linearity::RightLinear => write!(f, "right-linear"),
+            Linearity::LeftLinear => write!(f, "left-linear"),
+        }
+    }
+}
+
+impl Display for RegexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid regex at position {}: {}", self.position + 1, self.message)
+    }
+}
+
+impl Error for RegexError {}
+
+/// Returns whether every production of the grammar is right-linear or every
+/// one is left-linear, right-linearity is checked first
+///
+/// # Arguments
+///
+/// * `g` - Grammar to check
+pub fn get_linearity(g: &Grammar) -> Option<Linearity> {
+    let non_term_positions: Vec<(usize, Vec<usize>)> = g
+        .productions
+        .iter()
+        .map(|prod| {
+            let body = body(prod);
+            let positions = body
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| v.kind == SymbolsKind::NONTERM)
+                .map(|(i, _)| i)
+                .collect();
+            (body.len(), positions)
+        })
+        .collect();
+
+    let is_linear = |position: &dyn Fn(usize) -> usize| {
+        non_term_positions.iter().all(|(len, positions)| match positions.as_slice() {
+            [] => true,
+            [i] => *i == position(*len),
+            _ => false,
+        })
+    };
+
+    if is_linear(&|len| len - 1) {
+        Some(Linearity::RightLinear)
+    } else if is_linear(&|_| 0) {
+        Some(Linearity::LeftLinear)
+    } else {
+        None
+    }
+}
+
+/// Returns right-linear grammar of the language of the regex. Non-terminal
+/// Qi stands for state i of the DFA built from the regex
+///
+/// # Arguments
+///
+/// * `regex` - Regex in regex2fsm syntax: symbols, |, *, parentheses and \ escapes
+pub fn regex_to_grammar(regex: &str) -> Result<Grammar, RegexError> {
+    Ok(Nfa::from_regex(regex)?.determinize().to_right_linear())
+}
+
+/// Writes the NFA in the automaton file format of regex2fsm
+///
+/// # Arguments
+///
+/// * `nfa` - Automaton to write
+///
+/// * `filename` - Output file
+pub fn write_nfa_to_file(nfa: &Nfa, filename: &str) -> Result<(), Box<dyn Error>> {
+    // Names are separated by whitespace, '#' starts a comment and ':' a section
+    let invalid = nfa.states.iter().chain(nfa.alphabet.iter()).find(|v| {
+        v.is_empty() || v.chars().any(|c| c.is_whitespace() || c == '#' || c == ':')
+    });
+    if let Some(name) = invalid {
+        return Err(format!("{} can't be written in regex2fsm automaton format", name).into());
+    }
+    if nfa.alphabet.iter().any(|v| *v == EPSILON_SYMBOL.to_string()) {
+        return Err(format!("Symbol {} is reserved for epsilon in regex2fsm automaton format", EPSILON_SYMBOL).into());
+    }
+
+    fs::write(filename, nfa.to_string())?;
+
+    Ok(())
+}
+
+fn body(prod: &Production) -> Vec<&Symbol> {
+    prod.expression.iter().filter(|v| v.kind != SymbolsKind::EPSILON).collect()
+}
+
+impl Nfa {
+    /// Returns NFA of the language of right- or left-linear grammar, None
+    /// for other grammars. Non-terminals are states, productions with
+    /// several terminals go through intermediate states
+    ///
+    /// # Arguments
+    ///
+    /// * `g` - Grammar to convert
+    pub fn from_grammar(g: &Grammar) -> Option<Nfa> {
+        let linearity = get_linearity(g)?;
+
+        let mut nfa = Nfa::new(g.terms.iter().cloned().collect());
+        let mut used: BTreeSet<String> = g.non_terms.clone();
+        let index: BTreeMap<&String, usize> = g.non_terms.iter().map(|v| (v, nfa.add_state(v.clone()))).collect();
+
+        // Right-linear grammar derives words left to right ending in the
+        // final state, left-linear one right to left from the start state
+        let extra = match linearity {
+            Linearity::RightLinear => {
+                let extra = nfa.add_state(unique_name(String::from("F"), &mut used));
+                nfa.start = index[&g.start];
+                nfa.is_terminal.insert(extra);
+                extra
+            }
+            Linearity::LeftLinear => {
+                let extra = nfa.add_state(unique_name(String::from("S0"), &mut used));
+                nfa.start = extra;
+                nfa.is_terminal.insert(index[&g.start]);
+                extra
+            }
+        };
+
+        for prod in g.productions.iter() {
+            let lhs = index[&prod.replaced_symbol.value];
+            let body = body(prod);
+
+            let non_term = body
+                .iter()
+                .find(|v| v.kind == SymbolsKind::NONTERM)
+                .map(|v| index[&v.value]);
+            let word: Vec<&String> = body
+                .iter()
+                .filter(|v| v.kind == SymbolsKind::TERM)
+                .map(|v| &v.value)
+                .collect();
+
+            let (from, to) = match linearity {
+                Linearity::RightLinear => (lhs, non_term.unwrap_or(extra)),
+                Linearity::LeftLinear => (non_term.unwrap_or(extra), lhs),
+            };
+
+            nfa.add_path(from, &word, to, &prod.replaced_symbol.value, &mut used);
+        }
+
+        Some(nfa)
+    }
+
+    /// Returns Thompson NFA of the regex
+    ///
+    /// # Arguments
+    ///
+    /// * `regex` - Regex in regex2fsm syntax: symbols, |, *, parentheses and \ escapes
+    pub fn from_regex(regex: &str) -> Result<Nfa, RegexError> {
+        let mut parser = RegexParser { chars: regex.chars().collect(), position: 0 };
+        let tree = parser.parse_alt()?;
+
+        if parser.position < parser.chars.len() {
+            return Err(RegexError { position: parser.position, message: String::from("unmatched )") });
+        }
+
+        let mut alphabet: BTreeSet<String> = BTreeSet::new();
+        tree.collect_symbols(&mut alphabet);
+
+        let mut nfa = Nfa::new(alphabet.into_iter().collect());
+        let (start, end) = nfa.add_regex(&tree);
+        nfa.start = start;
+        nfa.is_terminal.insert(end);
+
+        Ok(nfa)
+    }
+
+    /// Returns DFA built by subset construction: every DFA state is an
+    /// epsilon-closed set of NFA states, state 0 is the closure of the start
+    pub fn determinize(&self) -> Dfa {
+        let mut dfa = Dfa {
+            alphabet: self.alphabet.clone(),
+            states: vec![],
+            trans: vec![],
+            is_terminal: BTreeSet::new(),
+        };
+
+        let mut numbers: BTreeMap<Vec<usize>, usize> = BTreeMap::new();
+        let start = self.epsilon_closure(&[self.start]);
+        numbers.insert(start.clone(), 0);
+        dfa.add_state(start.clone(), self.contains_terminal(&start));
+
+        let mut current = 0;
+        while current < dfa.states.len() {
+            for symbol in 0..self.alphabet.len() {
+                let targets: Vec<usize> = dfa.states[current]
+                    .iter()
+                    .flat_map(|state| self.trans[*state][symbol].iter().cloned())
+                    .collect();
+
+                if targets.is_empty() {
+                    continue;
+                }
+
+                let target = self.epsilon_closure(&targets);
+                let number = match numbers.get(&target) {
+                    Some(v) => *v,
+                    None => {
+                        numbers.insert(target.clone(), dfa.states.len());
+                        dfa.add_state(target.clone(), self.contains_terminal(&target))
+                    }
+                };
+
+                dfa.trans[current][symbol] = Some(number);
+            }
+
+            current += 1;
+        }
+
+        dfa
+    }
+
+    fn new(alphabet: Vec<String>) -> Nfa {
+        Nfa {
+            alphabet,
+            states: vec![],
+            trans: vec![],
+            epsilon_trans: vec![],
+            start: 0,
+            is_terminal: BTreeSet::new(),
+        }
+    }
+
+    fn add_state(&mut self, name: String) -> usize {
+        self.states.push(name);
+        self.trans.push(vec![vec![]; self.alphabet.len()]);
+        self.epsilon_trans.push(vec![]);
+
+        self.states.len() - 1
+    }
+
+    // None symbol is an epsilon move
+    fn add_transition(&mut self, from: usize, symbol: Option<&String>, to: usize) {
+        let targets = match symbol {
+            Some(symbol) => {
+                let column = self.alphabet.iter().position(|v| v == symbol).unwrap();
+                &mut self.trans[from][column]
+            }
+            None => &mut self.epsilon_trans[from],
+        };
+
+        if !targets.contains(&to) {
+            targets.push(to);
+        }
+    }
+
+    // Reads the word on the way from one state to another, intermediate
+    // states are named after the non-terminal
+    fn add_path(&mut self, from: usize, word: &[&String], to: usize, name: &str, used: &mut BTreeSet<String>) {
+        if word.is_empty() {
+            return self.add_transition(from, None, to);
+        }
+
+        let mut current = from;
+        for (i, symbol) in word.iter().enumerate() {
+            let next = match i + 1 == word.len() {
+                true => to,
+                _ => self.add_state(unique_name(name.to_string(), used)),
+            };
+
+            self.add_transition(current, Some(symbol), next);
+            current = next;
+        }
+    }
+
+    // Fragment of Thompson construction with its start and end states
+    fn add_regex(&mut self, regex: &Regex) -> (usize, usize) {
+        let new_state = |nfa: &mut Nfa| {
+            let name = format!("q{}", nfa.states.len());
+            nfa.add_state(name)
+        };
+
+        match regex {
+            Regex::Empty => {
+                let (start, end) = (new_state(self), new_state(self));
+                self.add_transition(start, None, end);
+                (start, end)
+            }
+            Regex::Char(symbol) => {
+                let (start, end) = (new_state(self), new_state(self));
+                self.add_transition(start, Some(symbol), end);
+                (start, end)
+            }
+            Regex::Concat(items) => {
+                let fragments: Vec<(usize, usize)> = items.iter().map(|v| self.add_regex(v)).collect();
+                for pair in fragments.windows(2) {
+                    self.add_transition(pair[0].1, None, pair[1].0);
+                }
+                (fragments[0].0, fragments[fragments.len() - 1].1)
+            }
+            Regex::Alt(items) => {
+                let start = new_state(self);
+                let fragments: Vec<(usize, usize)> = items.iter().map(|v| self.add_regex(v)).collect();
+                let end = new_state(self);
+                for (first, last) in fragments {
+                    self.add_transition(start, None, first);
+                    self.add_transition(last, None, end);
+                }
+                (start, end)
+            }
+            Regex::Star(item) => {
+                let start = new_state(self);
+                let (first, last) = self.add_regex(item);
+                let end = new_state(self);
+                self.add_transition(start, None, first);
+                self.add_transition(start, None, end);
+                self.add_transition(last, None, first);
+                self.add_transition(last, None, end);
+                (start, end)
+            }
+        }
+    }
+
+    fn epsilon_closure(&self, states: &[usize]) -> Vec<usize> {
+        let mut closure: BTreeSet<usize> = states.iter().cloned().collect();
+        let mut stack: Vec<usize> = states.to_vec();
+
+        while let Some(state) = stack.pop() {
+            for next in self.epsilon_trans[state].iter() {
+                if closure.insert(*next) {
+                    stack.push(*next);
+                }
+            }
+        }
+
+        closure.into_iter().collect()
+    }
+
+    fn contains_terminal(&self, states: &[usize]) -> bool {
+        states.iter().any(|v| self.is_terminal.contains(v))
+    }
+}
+
+impl Dfa {
+    /// Returns right-linear grammar with non-terminal Qi for every state i,
+    /// production Qi -> a Qj for every transition and Qi -> & for every
+    /// terminal state. Q0 is the start symbol
+    pub fn to_right_linear(&self) -> Grammar {
+        let name = |state: usize| format!("Q{}", state);
+        let origin = Origin::default().derived("regex");
+        let mut productions: Vec<Production> = vec![];
+
+        for (state, targets) in self.trans.iter().enumerate() {
+            let lhs = Symbol { kind: SymbolsKind::NONTERM, value: name(state) };
+
+            for (symbol, target) in targets.iter().enumerate() {
+                if let Some(target) = target {
+                    productions.push(Production {
+                        replaced_symbol: lhs.clone(),
+                        expression: vec![
+                            Symbol { kind: SymbolsKind::TERM, value: self.alphabet[symbol].clone() },
+                            Symbol { kind: SymbolsKind::NONTERM, value: name(*target) },
+                        ],
+                        origin: origin.clone(),
+                    });
+                }
+            }
+
+            if self.is_terminal.contains(&state) {
+                productions.push(Production {
+                    replaced_symbol: lhs,
+                    expression: vec![Symbol { kind: SymbolsKind::EPSILON, value: EPSILON_SYMBOL.to_string() }],
+                    origin: origin.clone(),
+                });
+            }
+        }
+
+        Grammar::new(
+            (0..self.states.len()).map(name).collect(),
+            self.alphabet.iter().cloned().collect(),
+            productions,
+            name(0),
+        )
+    }
+
+    /// Renders the automaton in dot format to the file
+    pub fn render_to(&self, output: &str) {
+        let mut f = File::create(output).unwrap();
+        dot::render(self, &mut f).unwrap()
+    }
+
+    fn add_state(&mut self, states: Vec<usize>, is_terminal: bool) -> usize {
+        self.states.push(states);
+        self.trans.push(vec![None; self.alphabet.len()]);
+
+        if is_terminal {
+            self.is_terminal.insert(self.states.len() - 1);
+        }
+
+        self.states.len() - 1
+    }
+}
+
+impl RegexParser {
+    // alt := concat ('|' concat)*
+    fn parse_alt(&mut self) -> Result<Regex, RegexError> {
+        let mut items = vec![self.parse_concat()?];
+
+        while self.peek() == Some('|') {
+            self.position += 1;
+            items.push(self.parse_concat()?);
+        }
+
+        match items.len() {
+            1 => Ok(items.pop().unwrap()),
+            _ => Ok(Regex::Alt(items)),
+        }
+    }
+
+    // concat := repeat*
+    fn parse_concat(&mut self) -> Result<Regex, RegexError> {
+        let mut items: Vec<Regex> = vec![];
+
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            items.push(self.parse_repeat()?);
+        }
+
+        match items.len() {
+            0 => Ok(Regex::Empty),
+            1 => Ok(items.pop().unwrap()),
+            _ => Ok(Regex::Concat(items)),
+        }
+    }
+
+    // repeat := atom '*'*
+    fn parse_repeat(&mut self) -> Result<Regex, RegexError> {
+        let mut atom = self.parse_atom()?;
+
+        while self.peek() == Some('*') {
+            self.position += 1;
+            atom = Regex::Star(Box::new(atom));
+        }
+
+        Ok(atom)
+    }
+
+    // atom := symbol | '\' any | '(' alt ')'
+    fn parse_atom(&mut self) -> Result<Regex, RegexError> {
+        let position = self.position;
+        let error = |message: String| Err(RegexError { position, message });
+
+        let c = self.peek().unwrap();
+        self.position += 1;
+
+        match c {
+            '(' => {
+                let inner = self.parse_alt()?;
+                if self.peek() != Some(')') {
+                    return error(String::from("unclosed ("));
+                }
+                self.position += 1;
+                Ok(inner)
+            }
+            '\\' => match self.peek() {
+                Some(c) => {
+                    self.position += 1;
+                    Ok(Regex::Char(c.to_string()))
+                }
+                None => error(String::from("nothing to escape")),
+            },
+            '*' => error(String::from("nothing to repeat")),
+            c if RESERVED_SYMBOLS.contains(c) => error(format!("symbol {} is reserved, escape it with \\", c)),
+            c => Ok(Regex::Char(c.to_string())),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).cloned()
+    }
+}
+
+impl Regex {
+    fn collect_symbols(&self, symbols: &mut BTreeSet<String>) {
+        match self {
+            Regex::Empty => (),
+            Regex::Char(symbol) => {
+                symbols.insert(symbol.clone());
+            }
+            Regex::Concat(items) | Regex::Alt(items) => items.iter().for_each(|v| v.collect_symbols(symbols)),
+            Regex::Star(item) => item.collect_symbols(symbols),
+        }
+    }
+}
+
+impl Display for Nfa {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = |states: &mut dyn Iterator<Item = &usize>| -> String {
+            states.map(|v| self.states[*v].as_str()).collect::<Vec<&str>>().join(" ")
+        };
+
+        writeln!(f, "states: {}", self.states.join(" "))?;
+        writeln!(f, "alphabet: {}", self.alphabet.join(" "))?;
+        writeln!(f, "start: {}", self.states[self.start])?;
+        writeln!(f, "finals: {}", names(&mut self.is_terminal.iter()))?;
+
+        for (state, name) in self.states.iter().enumerate() {
+            for (symbol, targets) in self.alphabet.iter().zip(self.trans[state].iter()) {
+                if !targets.is_empty() {
+                    writeln!(f, "{} {} {}", name, symbol, names(&mut targets.iter()))?;
+                }
+            }
+
+            if !self.epsilon_trans[state].is_empty() {
+                writeln!(f, "{} {} {}", name, EPSILON_SYMBOL, names(&mut self.epsilon_trans[state].iter()))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Transition table as printed by regex2fsm: -> marks the start state,
+// * terminal states and - missing transitions
+impl Display for Dfa {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut rows: Vec<Vec<String>> =
+            vec![std::iter::once(String::from("State")).chain(self.alphabet.iter().cloned()).collect()];
+
+        for (state, targets) in self.trans.iter().enumerate() {
+            let mut label = String::new();
+            if state == 0 {
+                label += "->";
+            }
+            if self.is_terminal.contains(&state) {
+                label += "*";
+            }
+            label += &state.to_string();
+
+            let mut row = vec![label];
+            row.extend(targets.iter().map(|target| match target {
+                Some(target) => target.to_string(),
+                None => String::from("-"),
+            }));
+            rows.push(row);
+        }
+
+        write_rows(f, &rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Membership in the language of a test regex
+    type Language = fn(&str) -> bool;
+
+    fn dfa_accepts(dfa: &Dfa, word: &[char]) -> bool {
+        let mut state = 0;
+
+        for c in word.iter() {
+            let symbol = match dfa.alphabet.iter().position(|v| *v == c.to_string()) {
+                Some(v) => v,
+                None => return false,
+            };
+            state = match dfa.trans[state][symbol] {
+                Some(v) => v,
+                None => return false,
+            };
+        }
+
+        dfa.is_terminal.contains(&state)
+    }
+
+    // All words over the alphabet up to the length
+    fn words(alphabet: &[char], max_len: usize) -> Vec<Vec<char>> {
+        let mut words: Vec<Vec<char>> = vec![vec![]];
+        let mut last: Vec<Vec<char>> = vec![vec![]];
+
+        for _ in 0..max_len {
+            last = last
+                .iter()
+                .flat_map(|prefix| alphabet.iter().map(move |c| [prefix.clone(), vec![*c]].concat()))
+                .collect();
+            words.extend(last.iter().cloned());
+        }
+
+        words
+    }
+
+    #[test]
+    fn grammar_of_regex_accepts_language_of_regex() {
+        let cases: [(&str, Language); 5] = [
+            ("a(b|c)*", |w| w.starts_with('a') && w[1..].chars().all(|c| c == 'b' || c == 'c')),
+            ("(ab)*|c", |w| w == "c" || (w.len() % 2 == 0 && w.as_bytes().chunks(2).all(|v| v == b"ab"))),
+            ("a*b*", |w| w.trim_start_matches('a').chars().all(|c| c == 'b')),
+            ("(a|b)*abb", |w| w.ends_with("abb") && w.chars().all(|c| c == 'a' || c == 'b')),
+            ("a\\*|()", |w| w.is_empty() || w == "a*"),
+        ];
+
+        for (regex, expected) in cases.iter() {
+            let g = regex_to_grammar(regex).unwrap();
+            assert_eq!(get_linearity(&g), Some(Linearity::RightLinear), "{}", regex);
+
+            let dfa = Nfa::from_grammar(&g).unwrap().determinize();
+
+            let alphabet: Vec<char> = "abc*".chars().collect();
+            for word in words(&alphabet, 6) {
+                let w: String = word.iter().collect();
+                assert_eq!(dfa_accepts(&dfa, &word), expected(&w), "{} on {:?}", regex, w);
+            }
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use crate::grammar::transformations::get_nullable_non_terms;
+use crate::grammar::{Grammar, Symbol, SymbolsKind, END_MARKER_SYMBOL, EPSILON_SYMBOL};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+
+/// FIRST and FOLLOW sets of grammar non-terminals
+#[derive(Debug, Serialize)]
+pub struct FirstFollow {
+    #[serde(rename = "first")]
+    pub first: BTreeMap<String, BTreeSet<String>>,
+    #[serde(rename = "follow")]
+    pub follow: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Grammar extended with FIRST and FOLLOW sets for JSON output
+#[derive(Serialize)]
+pub struct GrammarWithSets<'a> {
+    #[serde(flatten)]
+    pub grammar: &'a Grammar,
+    #[serde(flatten)]
+    pub sets: &'a FirstFollow,
+}
+
+impl FirstFollow {
+    /// Returns FIRST and FOLLOW sets of every grammar non-terminal.
+    /// FIRST set contains epsilon symbol if non-terminal is nullable,
+    /// FOLLOW set of start symbol contains end marker
+    ///
+    /// # Arguments
+    ///
+    /// * `g` - Grammar to compute sets for
+    pub fn new(g: &Grammar) -> FirstFollow {
+        let mut sets = FirstFollow {
+            first: get_first_sets(g),
+            follow: BTreeMap::new(),
+        };
+
+        sets.follow = get_follow_sets(g, &sets);
+
+        sets
+    }
+
+    /// Returns FIRST set of sentential form. Empty form produces epsilon
+    pub fn first_of(&self, expression: &[Symbol]) -> BTreeSet<String> {
+        first_of(&self.first, expression)
+    }
+}
+
+fn first_of(first: &BTreeMap<String, BTreeSet<String>>, expression: &[Symbol]) -> BTreeSet<String> {
+    let epsilon = EPSILON_SYMBOL.to_string();
+    let mut result: BTreeSet<String> = BTreeSet::new();
+
+    for symbol in expression.iter() {
+        match symbol.kind {
+            SymbolsKind::TERM => {
+                result.insert(symbol.value.clone());
+                return result;
+            }
+            SymbolsKind::EPSILON => continue,
+            SymbolsKind::NONTERM => {
+                let symbol_first = match first.get(&symbol.value) {
+                    Some(v) => v,
+                    None => return result,
+                };
+
+                result.extend(symbol_first.iter().filter(|v| **v != epsilon).cloned());
+
+                if !symbol_first.contains(&epsilon) {
+                    return result;
+                }
+            }
+        }
+    }
+
+    result.insert(epsilon);
+
+    result
+}
+
+fn get_first_sets(g: &Grammar) -> BTreeMap<String, BTreeSet<String>> {
+    let nullable = get_nullable_non_terms(g);
+
+    let mut first: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+    for non_term in g.non_terms.iter() {
+        let mut non_term_first = BTreeSet::new();
+        if nullable.contains(non_term) {
+            non_term_first.insert(EPSILON_SYMBOL.to_string());
+        }
+        first.insert(non_term.clone(), non_term_first);
+    }
+
+    loop {
+        let mut changed = false;
+
+        for prod in g.productions.iter() {
+            let prod_first = first_of(&first, &prod.expression);
+
+            if let Some(lhs_first) = first.get_mut(&prod.replaced_symbol.value) {
+                for symbol in prod_first.into_iter() {
+                    changed |= lhs_first.insert(symbol);
+                }
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    first
+}
+
+fn get_follow_sets(g: &Grammar, sets: &FirstFollow) -> BTreeMap<String, BTreeSet<String>> {
+    let epsilon = EPSILON_SYMBOL.to_string();
+
+    let mut follow: BTreeMap<String, BTreeSet<String>> = g
+        .non_terms
+        .iter()
+        .map(|non_term| (non_term.clone(), BTreeSet::new()))
+        .collect();
+
+    if let Some(start_follow) = follow.get_mut(&g.start) {
+        start_follow.insert(END_MARKER_SYMBOL.to_string());
+    }
+
+    loop {
+        let mut changed = false;
+
+        for prod in g.productions.iter() {
+            for (i, symbol) in prod.expression.iter().enumerate() {
+                if symbol.kind != SymbolsKind::NONTERM {
+                    continue;
+                }
+
+                // A -> alpha B beta: FIRST(beta) without epsilon is in FOLLOW(B),
+                // FOLLOW(A) is in FOLLOW(B) if beta is nullable
+                let mut extension = sets.first_of(&prod.expression[i + 1..]);
+                if extension.remove(&epsilon) {
+                    extension.extend(
+                        follow
+                            .get(&prod.replaced_symbol.value)
+                            .cloned()
+                            .unwrap_or_default(),
+                    );
+                }
+
+                if let Some(symbol_follow) = follow.get_mut(&symbol.value) {
+                    for v in extension.into_iter() {
+                        changed |= symbol_follow.insert(v);
+                    }
+                }
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    follow
+}
+
+fn display_set(h: Option<&BTreeSet<String>>) -> String {
+    h.map(|v| v.iter().cloned().collect::<Vec<_>>().join(" ")).unwrap_or_default()
+}
+
+impl Display for FirstFollow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let non_terms: Vec<&String> = self.first.keys().collect();
+
+        let rows: Vec<(&String, String, String)> = non_terms
+            .into_iter()
+            .map(|non_term| {
+                (
+                    non_term,
+                    display_set(self.first.get(non_term)),
+                    display_set(self.follow.get(non_term)),
+                )
+            })
+            .collect();
+
+        let non_term_width = rows.iter().map(|r| r.0.chars().count()).fold("Non-Terminal".len(), usize::max);
+        let first_width = rows.iter().map(|r| r.1.chars().count()).fold("FIRST".len(), usize::max);
+
+        writeln!(f, "{:<w1$} | {:<w2$} | FOLLOW", "Non-Terminal", "FIRST", w1 = non_term_width, w2 = first_width)?;
+
+        for (non_term, first, follow) in rows.iter() {
+            writeln!(f, "{:<w1$} | {:<w2$} | {}", non_term, first, follow, w1 = non_term_width, w2 = first_width)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar::bnf;
+
+    const EXPRESSION: &str = "E -> T E'\nE' -> \"+\" T E' | &\nT -> F T'\nT' -> \"*\" F T' | &\nF -> \"(\" E \")\" | id";
+
+    fn set(values: &[&str]) -> BTreeSet<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn first_and_follow_of_expression_grammar() {
+        let sets = FirstFollow::new(&bnf::parse(EXPRESSION).unwrap());
+
+        assert_eq!(sets.first["E"], set(&["(", "id"]));
+        assert_eq!(sets.first["E'"], set(&["+", "&"]));
+        assert_eq!(sets.first["T"], set(&["(", "id"]));
+        assert_eq!(sets.first["T'"], set(&["*", "&"]));
+        assert_eq!(sets.first["F"], set(&["(", "id"]));
+
+        assert_eq!(sets.follow["E"], set(&[")", "$"]));
+        assert_eq!(sets.follow["E'"], set(&[")", "$"]));
+        assert_eq!(sets.follow["T"], set(&["+", ")", "$"]));
+        assert_eq!(sets.follow["T'"], set(&["+", ")", "$"]));
+        assert_eq!(sets.follow["F"], set(&["*", "+", ")", "$"]));
+    }
+
+    #[test]
+    fn first_of_nullable_form_contains_epsilon() {
+        let g = bnf::parse(EXPRESSION).unwrap();
+        let sets = FirstFollow::new(&g);
+
+        let prod = g.productions.iter().find(|prod| prod.replaced_symbol.value == "E").unwrap();
+        assert_eq!(sets.first_of(&prod.expression[1..]), set(&["+", "&"]));
+        assert_eq!(sets.first_of(&[]), set(&["&"]));
+    }
+}
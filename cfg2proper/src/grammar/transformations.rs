@@ -1,16 +1,54 @@
-use crate::grammar::{Grammar, Production, Symbol, SymbolsKind, EPSILON_SYMBOL, U_CODEPOINTS};
+use crate::grammar::{Grammar, Origin, Production, Symbol, SymbolsKind, EPSILON_SYMBOL, U_CODEPOINTS};
 use itertools::Itertools;
-use std::collections::{HashMap, HashSet};
-use std::iter;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::error::Error;
+use std::{fmt, iter};
 
 const MAP_ELEMENT_NOT_FOUND_MSG: &str = "There must be at least one production for requested grammar non-terminal";
 
-pub fn get_productive(g: &Grammar) -> HashSet<String> {
-    let mut old_set: HashSet<String> = HashSet::new();
+/// Transformation which can't be applied to the grammar
+#[derive(Debug)]
+pub enum TransformationError {
+    /// Result of the transformation breaks invariants of the normal form
+    NotNormalForm(&'static str),
+    /// Transformation requires a grammar without left recursion
+    LeftRecursive(String),
+}
+
+impl fmt::Display for TransformationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TransformationError::NotNormalForm(form) =>
+                write!(f, "resulting grammar is not in {} normal form", form),
+            TransformationError::LeftRecursive(non_term) =>
+                write!(f, "grammar must be free of left recursion, but {} is left recursive, eliminate it with left-rec stage first", non_term),
+        }
+    }
+}
+
+impl Error for TransformationError {}
+
+/// Right side of a source production and positions of nullable symbols deleted from it
+pub type Erasure = (Vec<Symbol>, Vec<usize>);
+
+/// Productions of the source grammar hidden by `to_cnf`, which removes
+/// epsilon and unit productions on the way to Chomsky normal form
+#[derive(Debug, Clone, Default)]
+pub struct CnfTrace {
+    /// Right sides of source productions behind every production A -> X1 ... Xn
+    /// of the epsilon-free grammar, keyed by A and X1 ... Xn, each with positions
+    /// of nullable symbols deleted from it
+    pub erasures: BTreeMap<(String, Vec<String>), Vec<Erasure>>,
+    /// Unit productions A -> B of the epsilon-free grammar, which are removed
+    pub units: BTreeMap<String, BTreeSet<String>>,
+}
+
+pub fn get_productive(g: &Grammar) -> BTreeSet<String> {
+    let mut old_set: BTreeSet<String> = BTreeSet::new();
     old_set.insert(EPSILON_SYMBOL.to_string());
 
     _handle_n_e_loop(
-        |union: &mut HashSet<String>, old_set: &HashSet<String>| {
+        |union: &mut BTreeSet<String>, old_set: &BTreeSet<String>| {
             *union = iter::empty::<&String>()
                 .chain(union.union(old_set))
                 .chain(union.union(&g.terms))
@@ -25,12 +63,12 @@ pub fn get_productive(g: &Grammar) -> HashSet<String> {
 }
 
 // Provides non-terminals which lead to epsilon symbol
-pub fn get_nullable_non_terms(g: &Grammar) -> HashSet<String> {
-    let mut old_set: HashSet<_> = HashSet::new();
+pub fn get_nullable_non_terms(g: &Grammar) -> BTreeSet<String> {
+    let mut old_set: BTreeSet<_> = BTreeSet::new();
     old_set.insert(EPSILON_SYMBOL.to_string());
 
     _handle_n_e_loop(
-        |union: &mut HashSet<String>, old_set: &HashSet<String>| {
+        |union: &mut BTreeSet<String>, old_set: &BTreeSet<String>| {
             *union = union.union(old_set).cloned().collect();
         },
         g,
@@ -43,14 +81,14 @@ pub fn get_nullable_non_terms(g: &Grammar) -> HashSet<String> {
 fn _handle_n_e_loop<UnionClosure>(
     union_closure: UnionClosure,
     g: &Grammar,
-    old_set: &mut HashSet<String>,
+    old_set: &mut BTreeSet<String>,
 ) where
-    UnionClosure: Fn(&mut HashSet<String>, &HashSet<String>),
+    UnionClosure: Fn(&mut BTreeSet<String>, &BTreeSet<String>),
 {
     loop {
-        let mut new_set: HashSet<_> = HashSet::new();
+        let mut new_set: BTreeSet<_> = BTreeSet::new();
 
-        let mut union: HashSet<String> = HashSet::new();
+        let mut union: BTreeSet<String> = BTreeSet::new();
         union_closure(&mut union, old_set);
 
         for prod in g.productions.iter() {
@@ -58,7 +96,7 @@ fn _handle_n_e_loop<UnionClosure>(
                 .expression
                 .iter()
                 .map(|symbol| symbol.value.clone())
-                .collect::<HashSet<_>>();
+                .collect::<BTreeSet<_>>();
 
             if right.is_subset(&union) {
                 let left = &prod.replaced_symbol;
@@ -78,9 +116,9 @@ fn _handle_n_e_loop<UnionClosure>(
 
 pub fn remove_useless_symbols(g: &Grammar) -> Grammar {
     let n_e = get_productive(g);
-    let n1: HashSet<_> = g.non_terms.intersection(&n_e).cloned().collect();
+    let n1: BTreeSet<_> = g.non_terms.intersection(&n_e).cloned().collect();
 
-    let mut union: HashSet<_> = HashSet::new();
+    let mut union: BTreeSet<_> = BTreeSet::new();
     union = iter::empty::<&String>()
         .chain(union.union(&n_e))
         .chain(union.union(&g.terms))
@@ -91,7 +129,7 @@ pub fn remove_useless_symbols(g: &Grammar) -> Grammar {
         .productions
         .iter()
         .filter(|prod| {
-            let mut prod_symbols_set: HashSet<_> =
+            let mut prod_symbols_set: BTreeSet<_> =
                 prod.expression.iter().map(|v| v.value.clone()).collect();
 
             prod_symbols_set.insert(prod.replaced_symbol.value.clone());
@@ -106,9 +144,10 @@ pub fn remove_useless_symbols(g: &Grammar) -> Grammar {
     remove_unreachable(&g1)
 }
 
-pub fn remove_unreachable(g: &Grammar) -> Grammar {
-    let mut old_set: HashSet<String> = HashSet::new();
-    let mut new_set: HashSet<String> = [g.start.clone()].iter().cloned().collect();
+// Provides symbols which appear in sentential forms derived from start symbol
+pub fn get_reachable(g: &Grammar) -> BTreeSet<String> {
+    let mut old_set: BTreeSet<String> = BTreeSet::new();
+    let mut new_set: BTreeSet<String> = [g.start.clone()].iter().cloned().collect();
 
     while old_set != new_set {
         old_set = new_set.clone();
@@ -120,25 +159,31 @@ pub fn remove_unreachable(g: &Grammar) -> Grammar {
                             .iter()
                             .cloned()
                             .map(|symbol| symbol.value)
-                            .collect::<HashSet<String>>(),
+                            .collect::<BTreeSet<String>>(),
                     )
                 }
             }
         }
     }
 
+    new_set
+}
+
+pub fn remove_unreachable(g: &Grammar) -> Grammar {
+    let new_set = get_reachable(g);
+
     let new_productions: Vec<Production> = g
         .productions
         .iter()
         .filter(|prod| {
-            let mut production_symbols: HashSet<String> = HashSet::new();
+            let mut production_symbols: BTreeSet<String> = BTreeSet::new();
             production_symbols.insert(prod.replaced_symbol.value.clone());
             production_symbols.extend(
                 prod.expression
                     .iter()
                     .cloned()
                     .map(|v| v.value)
-                    .collect::<HashSet<String>>(),
+                    .collect::<BTreeSet<String>>(),
             );
 
             production_symbols.is_subset(&new_set)
@@ -149,11 +194,11 @@ pub fn remove_unreachable(g: &Grammar) -> Grammar {
     let new_non_terms = new_set
         .intersection(&g.non_terms)
         .cloned()
-        .collect::<HashSet<String>>();
+        .collect::<BTreeSet<String>>();
     let new_terms = new_set
         .intersection(&g.terms)
         .cloned()
-        .collect::<HashSet<String>>();
+        .collect::<BTreeSet<String>>();
 
     Grammar::new(new_non_terms, new_terms, new_productions, g.start.clone())
 }
@@ -170,22 +215,23 @@ pub fn to_e_free(g: &Grammar) -> Grammar {
 
     // Building new productions with compensated deleted non-terminals
     for prod in productions_without_epsilon.iter() {
-        compensate_epsilon_deletion(&mut new_productions, &prod, &n_e);
+        compensate_epsilon_deletion(&mut new_productions, prod, &n_e);
     }
 
-    let mut new_non_terms: HashSet<String> = g.non_terms.clone();
+    let mut new_non_terms: BTreeSet<String> = g.non_terms.clone();
 
     // S' -> S | e
     if n_e.contains(&g.start) {
         new_start = get_new_out_of(&g.start);
-        new_productions.push(Production::new(vec![
-            (SymbolsKind::NONTERM, new_start.clone()),
+        let alternatives = vec![
             (SymbolsKind::NONTERM, g.start.clone()),
-        ]));
-        new_productions.push(Production::new(vec![
-            (SymbolsKind::NONTERM, new_start.clone()),
             (SymbolsKind::EPSILON, EPSILON_SYMBOL.to_string()),
-        ]));
+        ];
+        for symbol in alternatives.into_iter() {
+            let mut prod = Production::new(vec![(SymbolsKind::NONTERM, new_start.clone()), symbol]);
+            prod.origin = Origin::default().derived("e-free");
+            new_productions.push(prod);
+        }
 
         new_non_terms.insert(new_start.clone());
     }
@@ -194,15 +240,36 @@ pub fn to_e_free(g: &Grammar) -> Grammar {
         new_non_terms,
         g.terms.clone(),
         new_productions,
-        new_start.into(),
+        new_start,
     )
 }
 
 fn compensate_epsilon_deletion(
     new_productions: &mut Vec<Production>,
     prod: &Production,
-    n_e: &HashSet<String>,
+    n_e: &BTreeSet<String>,
 ) {
+    for erased in get_erasures(prod, n_e).into_iter() {
+        //  In case we do not delete any nullable symbols
+        // Just append production to result
+        if erased.is_empty() {
+            new_productions.push(prod.clone());
+            continue;
+        }
+
+        let new_prod: Vec<(SymbolsKind, String)> = iter::once(&prod.replaced_symbol)
+            .chain(prod.expression.iter().enumerate().filter(|(i, _)| !erased.contains(i)).map(|(_, v)| v))
+            .map(|symbol| (symbol.kind, symbol.value.clone()))
+            .collect();
+        let mut new_prod = Production::new(new_prod);
+        new_prod.origin = prod.origin.derived("e-free");
+        new_productions.push(new_prod);
+    }
+}
+
+// Returns every combination of positions of nullable symbols which are deleted
+// from the production by to_e_free, the empty combination included
+fn get_erasures(prod: &Production, n_e: &BTreeSet<String>) -> Vec<Vec<usize>> {
     let nullable_idxs: Vec<_> = prod
         .expression
         .iter()
@@ -212,40 +279,19 @@ fn compensate_epsilon_deletion(
         .map(|numeration| numeration.0)
         .collect();
 
-    //  Since all symbols in the production are nullable
-    // Ignore it
-    if nullable_idxs.len() == prod.expression.len() {
-        return;
-    }
-
-    //  In case we do not have any nullable terms in production
-    // Just append it to result
-    if nullable_idxs.is_empty() {
-        new_productions.push(prod.clone());
+    //  If all symbols in the production are nullable
+    // deleting all of them at once would produce an empty production
+    let max_deleted = match !nullable_idxs.is_empty() && nullable_idxs.len() == prod.expression.len() {
+        true => nullable_idxs.len() - 1,
+        _ => nullable_idxs.len(),
+    };
 
-        return;
-    }
-
-    for i in 0..nullable_idxs.len() + 1 {
-        let mut prod_symbols: Vec<Symbol> = vec![prod.replaced_symbol.clone()];
-        prod_symbols.extend_from_slice(&prod.expression);
-        for comb in nullable_idxs.iter().combinations(i) {
-            let mut new_prod = prod_symbols.clone();
-            for idx in comb.into_iter() {
-                (new_prod.as_mut_slice())[1..][*idx].value = " ".to_string();
-            }
-
-            let new_prod: Vec<(SymbolsKind, String)> = new_prod
-                .into_iter()
-                .filter(|symbol| symbol.value != String::from(" "))
-                .map(|symbol| (symbol.kind, symbol.value))
-                .collect();
-            new_productions.push(Production::new(new_prod));
-        }
-    }
+    (0..max_deleted + 1)
+        .flat_map(|i| nullable_idxs.iter().cloned().combinations(i))
+        .collect()
 }
 
-fn remove_epsilon_productions(prods: &Vec<Production>) -> Vec<Production> {
+fn remove_epsilon_productions(prods: &[Production]) -> Vec<Production> {
     prods
         .iter()
         .filter(|prod| {
@@ -269,7 +315,7 @@ pub fn remove_unit_productions(g: &Grammar) -> Grammar {
 
     for prod in g.productions.iter() {
         if !is_unit_production(prod) {
-            extend_productions(&mut new_productions, &prod, &unit_chains)
+            extend_productions(&mut new_productions, prod, &unit_chains)
         }
     }
 
@@ -284,60 +330,71 @@ pub fn remove_unit_productions(g: &Grammar) -> Grammar {
 fn extend_productions(
     new_prods: &mut Vec<Production>,
     prod: &Production,
-    unit_chains: &HashMap<String, HashSet<String>>,
+    unit_chains: &BTreeMap<String, BTreeMap<String, Vec<String>>>,
 ) {
     let mut _new_prods = vec![];
-    //  iterates over each non-term's unit chain to find out
+    //  iterates over each non-term's unit chains to find out
     // if production is an end of the chain
-    for (non_term, chain) in unit_chains.into_iter() {
-        if non_term != &prod.replaced_symbol.value && chain.contains(&prod.replaced_symbol.value) {
-            _new_prods.push(Production {
-                replaced_symbol: Symbol {
-                    kind: SymbolsKind::NONTERM,
-                    value: (*non_term).clone(),
-                },
-                expression: prod.expression.clone(),
-            });
-        }
-    }
+    for (non_term, chains) in unit_chains.iter() {
+        let chain = match chains.get(&prod.replaced_symbol.value) {
+            Some(v) if non_term != &prod.replaced_symbol.value => v,
+            _ => continue,
+        };
+
+        // Every non-terminal of the chain is recorded, E -> T -> F -> id
+        // is substituted T and F
+        let origin = chain
+            .iter()
+            .fold(prod.origin.derived("unit"), |origin, v| origin.substituted("unit", v));
 
-    if !_new_prods.is_empty() {
-        new_prods.extend(_new_prods.into_iter());
-        return;
+        _new_prods.push(Production {
+            replaced_symbol: Symbol {
+                kind: SymbolsKind::NONTERM,
+                value: (*non_term).clone(),
+            },
+            expression: prod.expression.clone(),
+            origin,
+        });
     }
 
+    // Production is kept for its own non-term as well,
+    // since it may be reachable without the unit chain
     new_prods.push(prod.clone());
+    new_prods.extend(_new_prods);
 }
 
-fn detect_unit_productions(g: &Grammar) -> HashMap<String, HashSet<String>> {
-    // Contains unit non-terminals and unit production index
-    let mut unit_chains: HashMap<String, HashSet<String>> = HashMap::new();
+// Returns the shortest unit chain from every non-terminal to every non-terminal
+// derivable from it by unit productions, A -> B, B -> C gives A: {A: [], B: [B], C: [B, C]}
+fn detect_unit_productions(g: &Grammar) -> BTreeMap<String, BTreeMap<String, Vec<String>>> {
+    let mut unit_chains: BTreeMap<String, BTreeMap<String, Vec<String>>> = BTreeMap::new();
 
-    // Build non-terms chain for each non-term in G
+    // Build non-term chains of unit productions in breadth-first order
     for non_term in g.non_terms.iter() {
-        let mut old_set: HashSet<String> = HashSet::new();
-        old_set.insert((*non_term).clone());
+        let mut chains: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        chains.insert((*non_term).clone(), vec![]);
 
-        // Build non-term chain of unit production
-        loop {
-            let mut new_set: HashSet<String> = HashSet::new();
+        let mut queue: VecDeque<String> = VecDeque::new();
+        queue.push_back((*non_term).clone());
 
+        while let Some(from) = queue.pop_front() {
             for prod in g.productions.iter() {
-                if is_unit_production(&prod) && old_set.contains(&prod.replaced_symbol.value) {
-                    new_set.insert(prod.expression[0].value.clone());
+                if !is_unit_production(prod) || prod.replaced_symbol.value != from {
+                    continue;
                 }
-            }
 
-            new_set = new_set.union(&old_set).cloned().collect();
+                let to = &prod.expression[0].value;
+                if chains.contains_key(to) {
+                    continue;
+                }
 
-            if new_set == old_set {
-                break;
+                let mut chain = chains[&from].clone();
+                chain.push(to.clone());
+                chains.insert(to.clone(), chain);
+                queue.push_back(to.clone());
             }
-
-            old_set = new_set;
         }
 
-        unit_chains.insert((*non_term).clone(), old_set);
+        unit_chains.insert((*non_term).clone(), chains);
     }
 
     unit_chains
@@ -354,17 +411,32 @@ fn get_new_out_of(s: &String) -> String {
         }
     }
 
-    return s.to_string() + &String::from('\'');
+    s.to_string() + &String::from('\'')
+}
+
+// Returns s or a new symbol out of it which is not used yet and marks it as used
+pub fn get_fresh_out_of(s: &String, used_names: &mut BTreeSet<String>) -> String {
+    let mut new_symbol = s.to_string();
+    while used_names.contains(&new_symbol) {
+        new_symbol = get_new_out_of(&new_symbol);
+    }
+    used_names.insert(new_symbol.clone());
+
+    new_symbol
 }
 
 // Removes immediate left recursion within A productions
 // Returns Optional result with substituted symbol A' and
 // corresponding productions
-fn eliminate_immediate_lr(a_prods: &mut Vec<Production>) -> Option<(String, Vec<Production>)> {
+fn eliminate_immediate_lr(
+    a_prods: &mut Vec<Production>,
+    used_names: &mut BTreeSet<String>,
+) -> Option<(String, Vec<Production>)> {
     // Drain left recursive productions out of A productions
-    let recursive_prods: Vec<_> = a_prods.drain_filter(|prod|
-        prod.replaced_symbol.value == prod.expression[0].value
-    ).collect();
+    let (recursive_prods, rest): (Vec<_>, Vec<_>) = std::mem::take(a_prods)
+        .into_iter()
+        .partition(|prod| prod.replaced_symbol.value == prod.expression[0].value);
+    *a_prods = rest;
 
     // There is no immediate left recursion
     if recursive_prods.is_empty() {
@@ -372,7 +444,7 @@ fn eliminate_immediate_lr(a_prods: &mut Vec<Production>) -> Option<(String, Vec<
     }
 
     // Create substitution symbol for A
-    let a_sub_symbol = get_new_out_of(&recursive_prods[0].replaced_symbol.value);
+    let a_sub_symbol = get_fresh_out_of(&recursive_prods[0].replaced_symbol.value, used_names);
 
     // Extend beta productions with substituted A
     // beta1 | beta2 ... -> beta1 | beta2 | beta1 A' | beta2 A'...
@@ -386,6 +458,7 @@ fn eliminate_immediate_lr(a_prods: &mut Vec<Production>) -> Option<(String, Vec<
         .map(|mut prod| {
             prod.replaced_symbol.value = a_sub_symbol.to_string();
             prod.expression.remove(0);
+            prod.origin = prod.origin.derived("left-rec");
             prod
         })
         .collect();
@@ -400,23 +473,34 @@ fn eliminate_immediate_lr(a_prods: &mut Vec<Production>) -> Option<(String, Vec<
     Some((a_sub_symbol, sub_prods))
 }
 
-fn get_content_extended_by_sym(prods: &Vec<Production>, symbol: &String) -> Vec<Production> {
+fn get_content_extended_by_sym(prods: &[Production], symbol: &String) -> Vec<Production> {
     let mut prods_extension: Vec<Production> = vec![];
     for prod in prods.iter() {
         let mut extension = prod.clone();
         extension.expression
             .push(Symbol { kind: SymbolsKind::NONTERM, value: symbol.to_string() });
+        extension.origin = prod.origin.derived("left-rec");
         prods_extension.push(extension);
     }
 
     prods_extension
 }
 
-pub fn eliminate_indirect_lr(g: &Grammar) -> Grammar {
+// Eliminates left recursion substituting Aj into Ai -> Aj alpha for j < i, where
+// non-terminals are numbered in the given order. Non-terminals missing from
+// `order` follow it in order of their first appearance as a left side
+pub fn eliminate_indirect_lr(g: &Grammar, order: &[String]) -> Grammar {
     let mut mapping = map_productions_to_non_term(g);
-    let mut new_non_terms: Vec<String> = vec![];
+    let mut new_non_terms: BTreeMap<String, String> = BTreeMap::new();
+    let mut used_names: BTreeSet<String> = g.non_terms.union(&g.terms).cloned().collect();
 
-    let map_keys = mapping.keys().cloned().collect::<Vec<String>>();
+    let map_keys = order
+        .iter()
+        .filter(|v| mapping.contains_key(*v))
+        .chain(get_production_order(g).iter().filter(|v| !order.contains(v)))
+        .cloned()
+        .unique()
+        .collect::<Vec<String>>();
 
     for (i, i_value) in map_keys.iter().enumerate() {
         let mut ai_productions = mapping.get(i_value)
@@ -424,48 +508,71 @@ pub fn eliminate_indirect_lr(g: &Grammar) -> Grammar {
         for j_value in map_keys.iter().take(i) {
             let aj_productions = mapping.get(j_value)
                 .expect(MAP_ELEMENT_NOT_FOUND_MSG);
-            let ai2aj_prods = ai_productions
-                .drain_filter(|prod| prod.expression[0].value == *j_value)
-                .collect::<Vec<Production>>();
+            let (ai2aj_prods, rest): (Vec<_>, Vec<_>) = ai_productions
+                .into_iter()
+                .partition(|prod| prod.expression[0].value == *j_value);
+            ai_productions = rest;
             for mut prod in ai2aj_prods.into_iter() {
                 prod.expression.remove(0);
-                let extension = extend_from_front(aj_productions, &prod);
+                let extension = extend_from_front(aj_productions, &prod, "left-rec");
                 ai_productions.extend_from_slice(&extension);
             }
         }
-        let new_non_term = eliminate_immediate_lr(&mut ai_productions);
-        match new_non_term {
-            Some((new_non_term, prods)) => {
-                new_non_terms.push(new_non_term.to_string());
-                mapping.insert(new_non_term.to_string(), prods);
-            },
-            _ => ()
+        let new_non_term = eliminate_immediate_lr(&mut ai_productions, &mut used_names);
+        if let Some((new_non_term, prods)) = new_non_term {
+            new_non_terms.insert(i_value.to_string(), new_non_term.to_string());
+            mapping.insert(new_non_term.to_string(), prods);
         }
 
         mapping.insert(i_value.to_string(), ai_productions.clone());
     }
 
-    convert_mapping_to_grammar(&mapping, &g.terms, &g.start)
+    // Every A' follows its A
+    let order = map_keys
+        .iter()
+        .flat_map(|v| iter::once(v).chain(new_non_terms.get(v)))
+        .cloned()
+        .collect::<Vec<String>>();
+
+    convert_mapping_to_grammar(&mapping, &order, &g.terms, &g.start)
 }
 
-fn convert_mapping_to_grammar(mapping: &HashMap<String, Vec<Production>>, terms: &HashSet<String>, start: &String) -> Grammar {
-    let mut non_terms: HashSet<String> = HashSet::new();
+// Returns non-terminals in order of their first appearance as a left side,
+// non-terminals without productions follow in alphabetical order
+pub fn get_production_order(g: &Grammar) -> Vec<String> {
+    g.productions
+        .iter()
+        .map(|prod| &prod.replaced_symbol.value)
+        .chain(g.non_terms.iter())
+        .unique()
+        .cloned()
+        .collect()
+}
+
+fn convert_mapping_to_grammar(
+    mapping: &BTreeMap<String, Vec<Production>>,
+    order: &[String],
+    terms: &BTreeSet<String>,
+    start: &str,
+) -> Grammar {
+    let mut non_terms: BTreeSet<String> = BTreeSet::new();
     let mut productions: Vec<Production> = vec![];
-    for (non_term, prods) in mapping {
+    for non_term in order.iter() {
         non_terms.insert(non_term.to_string());
-        productions.extend_from_slice(prods);
+        productions.extend_from_slice(mapping.get(non_term).expect(MAP_ELEMENT_NOT_FOUND_MSG));
     }
 
-    Grammar::new(non_terms, terms.clone(), productions, start.clone())
+    Grammar::new(non_terms, terms.clone(), productions, start.to_string())
 }
 
 // Inserting l production to the front of all r productions
 // A -> l and B -> r1 | r2 | r3
 // A -> l r1 | l r2 | l r3
-fn extend_from_front(l: &Vec<Production>, r: &Production) -> Vec<Production> {
+// Result keeps the origin of r with the substituted non-terminal
+fn extend_from_front(l: &Vec<Production>, r: &Production, transformation: &str) -> Vec<Production> {
     let mut extension: Vec<Production> = vec![];
     for prod in l {
-        let extended_prod: Vec<Symbol> = vec![]
+        let extended_prod: Vec<Symbol> = []
             .iter()
             .chain(prod.expression.iter())
             .chain(r.expression.iter())
@@ -474,14 +581,15 @@ fn extend_from_front(l: &Vec<Production>, r: &Production) -> Vec<Production> {
         extension.push(Production {
             replaced_symbol: r.replaced_symbol.clone(),
             expression: extended_prod,
+            origin: r.origin.substituted(transformation, &prod.replaced_symbol.value),
         });
     }
 
     extension
 }
 
-fn map_productions_to_non_term(g: &Grammar) -> HashMap<String, Vec<Production>> {
-    let mut h: HashMap<String, Vec<_>> = HashMap::new();
+fn map_productions_to_non_term(g: &Grammar) -> BTreeMap<String, Vec<Production>> {
+    let mut h: BTreeMap<String, Vec<_>> = BTreeMap::new();
 
     for non_term in g.non_terms.iter() {
         let prods = g.productions
@@ -493,4 +601,590 @@ fn map_productions_to_non_term(g: &Grammar) -> HashMap<String, Vec<Production>>
     }
 
     h
-}
\ No newline at end of file
+}
+// Extracts longest common prefixes of alternatives into new non-terminals
+// A -> a b c | a b d | e -> A -> a b A', A' -> c | d. Groups with the same
+// set of suffixes share the new non-terminal
+pub fn left_factor(g: &Grammar) -> Grammar {
+    let mut non_terms = g.non_terms.clone();
+    let mut used_names: BTreeSet<String> = g.non_terms.union(&g.terms).cloned().collect();
+
+    let mut pending: VecDeque<(String, Vec<Production>)> = VecDeque::new();
+    for prod in g.productions.iter() {
+        match pending.iter_mut().find(|(non_term, _)| *non_term == prod.replaced_symbol.value) {
+            Some((_, prods)) => prods.push(prod.clone()),
+            None => pending.push_back((prod.replaced_symbol.value.clone(), vec![prod.clone()])),
+        }
+    }
+
+    // Suffixes of every factored group and the non-terminal deriving them
+    let mut factored: Vec<(Vec<Vec<Symbol>>, String)> = vec![];
+
+    let mut new_productions: Vec<Production> = vec![];
+
+    while let Some((non_term, non_term_prods)) = pending.pop_front() {
+        let mut prods: Vec<Production> = vec![];
+        for prod in non_term_prods.into_iter() {
+            if !prods.contains(&prod) {
+                prods.push(prod);
+            }
+        }
+
+        while let Some(group) = find_common_prefix_group(&prods) {
+            let prefix_len = get_common_prefix_len(&group);
+            let prefix = group[0].expression[..prefix_len].to_vec();
+
+            // Empty suffix is replaced by epsilon
+            let suffixes: Vec<Vec<Symbol>> = group
+                .iter()
+                .map(|prod| match prod.expression.len() == prefix_len {
+                    true => vec![Symbol { kind: SymbolsKind::EPSILON, value: EPSILON_SYMBOL.to_string() }],
+                    _ => prod.expression[prefix_len..].to_vec(),
+                })
+                .collect();
+
+            let same_suffixes = |other: &Vec<Vec<Symbol>>| {
+                other.len() == suffixes.len() && other.iter().all(|suffix| suffixes.contains(suffix))
+            };
+
+            let sub_symbol = match factored.iter().find(|(other, _)| same_suffixes(other)) {
+                Some((_, sub_symbol)) => sub_symbol.clone(),
+                None => {
+                    let sub_symbol = get_fresh_out_of(&non_term, &mut used_names);
+                    non_terms.insert(sub_symbol.clone());
+
+                    // A' -> suffix
+                    let sub_prods: Vec<Production> = group
+                        .iter()
+                        .zip(suffixes.iter())
+                        .map(|(prod, suffix)| Production {
+                            replaced_symbol: Symbol { kind: SymbolsKind::NONTERM, value: sub_symbol.clone() },
+                            expression: suffix.clone(),
+                            origin: prod.origin.derived("left-factor"),
+                        })
+                        .collect();
+
+                    pending.push_back((sub_symbol.clone(), sub_prods));
+                    factored.push((suffixes.clone(), sub_symbol.clone()));
+
+                    sub_symbol
+                }
+            };
+
+            // A -> prefix A' replaces the first of factored alternatives
+            let first_idx = prods.iter().position(|prod| *prod == group[0]).unwrap();
+            prods[first_idx].expression = prefix;
+            prods[first_idx]
+                .expression
+                .push(Symbol { kind: SymbolsKind::NONTERM, value: sub_symbol });
+            prods[first_idx].origin = prods[first_idx].origin.derived("left-factor");
+            prods.retain(|prod| !group[1..].contains(prod));
+        }
+
+        new_productions.extend(prods);
+    }
+
+    Grammar::new(non_terms, g.terms.clone(), new_productions, g.start.clone())
+}
+
+// Returns the first group of alternatives which start with the same symbol
+fn find_common_prefix_group(prods: &[Production]) -> Option<Vec<Production>> {
+    for (i, prod) in prods.iter().enumerate() {
+        let first = match prod.expression.first() {
+            Some(v) if v.kind != SymbolsKind::EPSILON => v,
+            _ => continue,
+        };
+
+        let group: Vec<Production> = prods[i..]
+            .iter()
+            .filter(|other| other.expression.first() == Some(first))
+            .cloned()
+            .collect();
+
+        if group.len() > 1 {
+            return Some(group);
+        }
+    }
+
+    None
+}
+
+fn get_common_prefix_len(prods: &[Production]) -> usize {
+    let mut len = 0;
+
+    while prods.iter().all(|prod| {
+        prod.expression.len() > len && prod.expression[len] == prods[0].expression[len]
+    }) {
+        len += 1;
+    }
+
+    len
+}
+
+// Converts grammar to Chomsky normal form, where every production is
+// A -> B C, A -> a or S -> & (if the language contains epsilon) and
+// start symbol doesn't appear on the right side
+pub fn to_cnf(g: &Grammar) -> Grammar {
+    to_cnf_traced(g).0
+}
+
+/// Returns Chomsky normal form of the grammar along with the trace of epsilon
+/// and unit productions removed from it, so that derivations of the normal form
+/// can be mapped back to derivations of the grammar
+///
+/// # Arguments
+///
+/// * `g` - Grammar to convert
+pub fn to_cnf_traced(g: &Grammar) -> (Grammar, CnfTrace) {
+    let g1 = to_e_free(g);
+    let g2 = remove_unit_productions(&g1);
+    let g3 = remove_useless_symbols(&g2);
+
+    let mut non_terms = g3.non_terms.clone();
+    let mut used_names: BTreeSet<String> = g3.non_terms.union(&g3.terms).cloned().collect();
+
+    let mut start = g3.start.clone();
+    let mut productions = g3.productions.clone();
+
+    // S0 -> alpha for every S -> alpha, so that start never appears on the right side
+    let start_on_right = productions.iter().any(|prod| prod.expression.iter().any(|v| v.value == start));
+    if start_on_right {
+        start = get_fresh_out_of(&g3.start, &mut used_names);
+        non_terms.insert(start.clone());
+
+        let start_productions: Vec<Production> = productions
+            .iter()
+            .filter(|prod| prod.replaced_symbol.value == g3.start)
+            .map(|prod| Production {
+                replaced_symbol: Symbol { kind: SymbolsKind::NONTERM, value: start.clone() },
+                expression: prod.expression.clone(),
+                origin: prod.origin.derived("cnf"),
+            })
+            .collect();
+        productions.extend(start_productions);
+    }
+
+    let proxied_productions = replace_terms_with_proxies(&productions, 0, &mut non_terms, &mut used_names, "cnf");
+
+    let mut new_productions: Vec<Production> = vec![];
+
+    for mut prod in proxied_productions.into_iter() {
+        // A -> X1 X2 ... Xn -> A -> X1 A1, A1 -> X2 A2, ... An-2 -> Xn-1 Xn
+        while prod.expression.len() > 2 {
+            let rest = get_fresh_out_of(&prod.replaced_symbol.value, &mut used_names);
+            non_terms.insert(rest.clone());
+
+            let tail = prod.expression.split_off(1);
+            prod.expression.push(Symbol { kind: SymbolsKind::NONTERM, value: rest.clone() });
+            prod.origin = prod.origin.derived("cnf");
+            let origin = prod.origin.clone();
+            new_productions.push(prod);
+
+            prod = Production {
+                replaced_symbol: Symbol { kind: SymbolsKind::NONTERM, value: rest },
+                expression: tail,
+                origin,
+            };
+        }
+
+        new_productions.push(prod);
+    }
+
+    (Grammar::new(non_terms, g3.terms.clone(), new_productions, start), trace_epsilon_deletion(g))
+}
+
+// Records source productions of every production to_e_free builds
+// out of the grammar, and unit productions among them
+fn trace_epsilon_deletion(g: &Grammar) -> CnfTrace {
+    let n_e = get_nullable_non_terms(g);
+    let mut trace = CnfTrace::default();
+
+    for prod in remove_epsilon_productions(&g.productions).iter() {
+        for erased in get_erasures(prod, &n_e).into_iter() {
+            let kept: Vec<&Symbol> = prod
+                .expression
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !erased.contains(i))
+                .map(|(_, v)| v)
+                .collect();
+
+            if let [v] = kept.as_slice() {
+                if v.kind == SymbolsKind::NONTERM {
+                    trace.units.entry(prod.replaced_symbol.value.clone()).or_default().insert(v.value.clone());
+                }
+            }
+
+            let key = (prod.replaced_symbol.value.clone(), kept.iter().map(|v| v.value.clone()).collect());
+            trace.erasures.entry(key).or_default().push((prod.expression.clone(), erased));
+        }
+    }
+
+    trace
+}
+
+// Replaces terminals of productions longer than one symbol, starting
+// from `from` position, with proxies T_a -> a. Returns productions with proxies
+fn replace_terms_with_proxies(
+    prods: &[Production],
+    from: usize,
+    non_terms: &mut BTreeSet<String>,
+    used_names: &mut BTreeSet<String>,
+    transformation: &str,
+) -> Vec<Production> {
+    let mut new_productions: Vec<Production> = vec![];
+    let mut proxy_productions: Vec<Production> = vec![];
+    let mut proxies: BTreeMap<String, String> = BTreeMap::new();
+
+    for prod in prods.iter() {
+        let mut prod = prod.clone();
+
+        let mut replaced = false;
+
+        if prod.expression.len() > 1 {
+            for symbol in prod.expression.iter_mut().skip(from).filter(|v| v.kind == SymbolsKind::TERM) {
+                let proxy = proxies.entry(symbol.value.clone()).or_insert_with(|| {
+                    let proxy = get_fresh_out_of(&(String::from("T_") + &symbol.value), used_names);
+                    non_terms.insert(proxy.clone());
+                    let mut proxy_prod = Production::new(vec![
+                        (SymbolsKind::NONTERM, proxy.clone()),
+                        (SymbolsKind::TERM, symbol.value.clone()),
+                    ]);
+                    proxy_prod.origin = Origin::default().derived(transformation);
+                    proxy_productions.push(proxy_prod);
+                    proxy
+                });
+
+                *symbol = Symbol { kind: SymbolsKind::NONTERM, value: proxy.clone() };
+                replaced = true;
+            }
+        }
+
+        if replaced {
+            prod.origin = prod.origin.derived(transformation);
+        }
+
+        new_productions.push(prod);
+    }
+
+    new_productions.extend(proxy_productions);
+
+    new_productions
+}
+
+// Checks Chomsky normal form invariants, start symbol can't appear on the right side
+pub fn is_cnf(g: &Grammar) -> bool {
+    g.productions.iter().all(|prod| {
+        let kinds: Vec<SymbolsKind> = prod.expression.iter().map(|v| v.kind).collect();
+
+        match kinds.as_slice() {
+            [SymbolsKind::TERM] => true,
+            [SymbolsKind::EPSILON] => prod.replaced_symbol.value == g.start,
+            [SymbolsKind::NONTERM, SymbolsKind::NONTERM] => prod.expression.iter().all(|v| v.value != g.start),
+            _ => false,
+        }
+    })
+}
+
+// Converts left recursion free grammar to Greibach normal form, where every
+// production is A -> a B1 ... Bn or S -> & (if the language contains epsilon)
+pub fn to_gnf(g: &Grammar) -> Result<Grammar, TransformationError> {
+    let mapping = map_productions_to_non_term(g);
+
+    // Back-substitution of leading non-terminals until every production
+    // starts with a terminal
+    let mut expanded: BTreeMap<String, Vec<Production>> = BTreeMap::new();
+    for non_term in g.non_terms.iter() {
+        expand_leading_non_term(non_term, &mapping, &mut expanded, &mut vec![])?;
+    }
+
+    let mut non_terms = g.non_terms.clone();
+    let mut used_names: BTreeSet<String> = g.non_terms.union(&g.terms).cloned().collect();
+
+    let substituted_productions: Vec<Production> = expanded.values().flatten().cloned().collect();
+    let new_productions = replace_terms_with_proxies(&substituted_productions, 1, &mut non_terms, &mut used_names, "gnf");
+
+    // Non-terminals which were used only at leading positions become unreachable
+    let gnf = remove_unreachable(&Grammar::new(non_terms, g.terms.clone(), new_productions, g.start.clone()));
+
+    // Epsilon productions of non-start symbols can't be expanded away
+    if !is_gnf(&gnf) {
+        return Err(TransformationError::NotNormalForm("Greibach"));
+    }
+
+    Ok(gnf)
+}
+
+fn expand_leading_non_term(
+    non_term: &String,
+    mapping: &BTreeMap<String, Vec<Production>>,
+    expanded: &mut BTreeMap<String, Vec<Production>>,
+    visiting: &mut Vec<String>,
+) -> Result<(), TransformationError> {
+    if expanded.contains_key(non_term) {
+        return Ok(());
+    }
+
+    if visiting.contains(non_term) {
+        return Err(TransformationError::LeftRecursive(non_term.to_string()));
+    }
+    visiting.push(non_term.to_string());
+
+    let mut prods: Vec<Production> = vec![];
+
+    for prod in mapping.get(non_term).expect(MAP_ELEMENT_NOT_FOUND_MSG).iter() {
+        let leading = &prod.expression[0];
+        if leading.kind != SymbolsKind::NONTERM {
+            prods.push(prod.clone());
+            continue;
+        }
+
+        // A -> B alpha and B -> beta1 | beta2 ... -> A -> beta1 alpha | beta2 alpha
+        expand_leading_non_term(&leading.value, mapping, expanded, visiting)?;
+
+        let mut rest = prod.clone();
+        rest.expression.remove(0);
+        for substitution in extend_from_front(&expanded[&leading.value], &rest, "gnf").into_iter() {
+            if !prods.contains(&substitution) {
+                prods.push(substitution);
+            }
+        }
+    }
+
+    visiting.pop();
+    expanded.insert(non_term.to_string(), prods);
+
+    Ok(())
+}
+
+// Checks Greibach normal form invariants
+pub fn is_gnf(g: &Grammar) -> bool {
+    // Start symbol can't appear on the right side if it produces epsilon
+    let nullable_start = g.productions.iter().any(|prod| {
+        prod.replaced_symbol.value == g.start && prod.expression.iter().any(|v| v.kind == SymbolsKind::EPSILON)
+    });
+
+    g.productions.iter().all(|prod| match prod.expression.split_first() {
+        Some((first, rest)) if first.kind == SymbolsKind::TERM => rest
+            .iter()
+            .all(|v| v.kind == SymbolsKind::NONTERM && (!nullable_start || v.value != g.start)),
+        Some((first, rest)) if first.kind == SymbolsKind::EPSILON => {
+            rest.is_empty() && prod.replaced_symbol.value == g.start
+        }
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    const GRAMMARS: [&[&str]; 6] = [
+        &["E -> E + T | T", "T -> T * F | F", "F -> ( E ) | i"],
+        &["S -> a S b | S S | &"],
+        &["S -> a S | b"],
+        &["S -> A | B | D", "A -> B | a", "B -> A | b S", "C -> c", "D -> d D"],
+        &["S -> A B", "A -> a A | &", "B -> b | &"],
+        &["S -> &"],
+    ];
+
+    // Returns grammar of the rules, the first rule is for the start symbol.
+    // Names starting with an upper case letter are non-terminals
+    fn grammar(rules: &[&str]) -> Grammar {
+        let mut productions: Vec<Production> = vec![];
+
+        for rule in rules.iter() {
+            let (lhs, alternatives) = rule.split_once("->").unwrap();
+            for alternative in alternatives.split('|') {
+                let mut symbols = vec![(SymbolsKind::NONTERM, lhs.trim().to_string())];
+                for name in alternative.split_whitespace() {
+                    let kind = match name {
+                        "&" => SymbolsKind::EPSILON,
+                        _ if name.starts_with(char::is_uppercase) => SymbolsKind::NONTERM,
+                        _ => SymbolsKind::TERM,
+                    };
+                    symbols.push((kind, name.to_string()));
+                }
+                productions.push(Production::new(symbols));
+            }
+        }
+
+        let names = |kind: SymbolsKind| {
+            productions
+                .iter()
+                .flat_map(|prod| iter::once(&prod.replaced_symbol).chain(prod.expression.iter()))
+                .filter(|v| v.kind == kind)
+                .map(|v| v.value.clone())
+                .collect()
+        };
+        let start = productions[0].replaced_symbol.value.clone();
+
+        Grammar::new(names(SymbolsKind::NONTERM), names(SymbolsKind::TERM), productions.clone(), start)
+    }
+
+    // Returns sentences of the language of the grammar up to the given length
+    fn language(g: &Grammar, max_len: usize) -> BTreeSet<Vec<String>> {
+        let mut derived: BTreeMap<String, BTreeSet<Vec<String>>> = BTreeMap::new();
+
+        loop {
+            let mut changed = false;
+
+            for prod in g.productions.iter() {
+                let mut sentences: BTreeSet<Vec<String>> = iter::once(vec![]).collect();
+                for symbol in prod.expression.iter() {
+                    let parts: BTreeSet<Vec<String>> = match symbol.kind {
+                        SymbolsKind::TERM => iter::once(vec![symbol.value.clone()]).collect(),
+                        SymbolsKind::EPSILON => iter::once(vec![]).collect(),
+                        SymbolsKind::NONTERM => derived.get(&symbol.value).cloned().unwrap_or_default(),
+                    };
+                    sentences = sentences
+                        .iter()
+                        .flat_map(|prefix| parts.iter().map(move |part| [prefix.clone(), part.clone()].concat()))
+                        .filter(|sentence| sentence.len() <= max_len)
+                        .collect();
+                }
+
+                let entry = derived.entry(prod.replaced_symbol.value.clone()).or_default();
+                for sentence in sentences.into_iter() {
+                    changed |= entry.insert(sentence);
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        derived.remove(&g.start).unwrap_or_default()
+    }
+
+    #[test]
+    fn cnf_has_only_binary_and_terminal_productions() {
+        for rules in GRAMMARS.iter() {
+            let cnf = to_cnf(&grammar(rules));
+
+            assert!(is_cnf(&cnf), "{:?}", rules);
+            for prod in cnf.productions.iter().filter(|prod| prod.replaced_symbol.value != cnf.start) {
+                let kinds: Vec<SymbolsKind> = prod.expression.iter().map(|v| v.kind).collect();
+                assert!(
+                    kinds == [SymbolsKind::NONTERM, SymbolsKind::NONTERM] || kinds == [SymbolsKind::TERM],
+                    "{:?}: {:?}",
+                    rules,
+                    prod
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn cnf_keeps_epsilon_only_for_nullable_language() {
+        for rules in GRAMMARS.iter() {
+            let g = grammar(rules);
+            let cnf = to_cnf(&g);
+
+            let epsilon_prods: Vec<&Production> = cnf
+                .productions
+                .iter()
+                .filter(|prod| prod.expression.iter().any(|v| v.kind == SymbolsKind::EPSILON))
+                .collect();
+
+            let derives_epsilon = language(&g, 0).contains(&vec![]);
+            assert_eq!(epsilon_prods.len(), derives_epsilon as usize, "{:?}", rules);
+            assert!(
+                epsilon_prods.iter().all(|prod| prod.replaced_symbol.value == cnf.start && prod.expression.len() == 1),
+                "{:?}",
+                rules
+            );
+        }
+    }
+
+    #[test]
+    fn cnf_start_is_never_on_right_side() {
+        for rules in GRAMMARS.iter() {
+            let cnf = to_cnf(&grammar(rules));
+
+            for prod in cnf.productions.iter() {
+                assert!(prod.expression.iter().all(|v| v.value != cnf.start), "{:?}: {:?}", rules, prod);
+            }
+        }
+    }
+
+    #[test]
+    fn cnf_preserves_language() {
+        for rules in GRAMMARS.iter() {
+            let g = grammar(rules);
+
+            assert_eq!(language(&to_cnf(&g), 6), language(&g, 6), "{:?}", rules);
+        }
+    }
+
+    #[test]
+    fn unit_removal_records_whole_chain() {
+        let g = remove_unit_productions(&grammar(GRAMMARS[0]));
+        let substituted = |lhs: &str| {
+            g.productions
+                .iter()
+                .find(|prod| prod.replaced_symbol.value == lhs && prod.expression[0].value == "i")
+                .map(|prod| prod.origin.substituted.clone())
+                .unwrap()
+        };
+
+        assert_eq!(substituted("E"), vec!["T", "F"]);
+        assert_eq!(substituted("T"), vec!["F"]);
+        assert!(substituted("F").is_empty());
+    }
+
+    #[test]
+    fn indirect_lr_elimination_is_deterministic() {
+        let rules = ["S -> A a | b", "A -> A c | S d | B e | f", "B -> S g | A h | i"];
+        let eliminate = |order: &[String]| {
+            let g = eliminate_indirect_lr(&grammar(&rules), order);
+            format!("{}\n{:?}", g, g.productions)
+        };
+
+        let order = get_production_order(&grammar(&rules));
+        assert_eq!(order, ["S", "A", "B"]);
+
+        let first = eliminate(&[]);
+        for _ in 0..10 {
+            assert_eq!(eliminate(&[]), first);
+        }
+        assert_eq!(eliminate(&order), first);
+
+        let reversed: Vec<String> = order.iter().rev().cloned().collect();
+        assert_eq!(eliminate(&reversed), eliminate(&reversed));
+        assert_ne!(eliminate(&reversed), first);
+    }
+
+    #[test]
+    fn left_factor_shares_non_terminal_of_same_suffixes() {
+        let g = grammar(&["S -> a b c | a b d | e x c | e x d | e"]);
+        let factored = left_factor(&g);
+
+        assert_eq!(factored.non_terms.len(), 3, "{}", factored);
+        assert_eq!(factored.productions.len(), 6, "{}", factored);
+        assert_eq!(language(&factored, 4), language(&g, 4));
+    }
+
+    #[test]
+    fn gnf_rejects_left_recursion() {
+        match to_gnf(&grammar(GRAMMARS[0])) {
+            Err(TransformationError::LeftRecursive(_)) => (),
+            v => panic!("Left recursion must be reported, got {:?}", v),
+        }
+    }
+
+    #[test]
+    fn gnf_of_proper_grammar_preserves_language() {
+        for rules in GRAMMARS.iter() {
+            let g = grammar(rules);
+            let proper = remove_unit_productions(&remove_useless_symbols(&to_e_free(&g)));
+            let order = get_production_order(&proper);
+            let gnf = to_gnf(&eliminate_indirect_lr(&proper, &order)).unwrap();
+
+            assert!(is_gnf(&gnf), "{:?}", rules);
+            assert_eq!(language(&gnf, 6), language(&g, 6), "{:?}", rules);
+        }
+    }
+}
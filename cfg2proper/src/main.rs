@@ -1,25 +1,497 @@
-#![feature(drain_filter)]
 mod grammar;
 
 #[macro_use]
 extern crate lazy_static;
 
 use structopt::StructOpt;
+use std::env;
+use std::ffi::OsString;
+use std::io::{self, BufRead};
+use std::iter;
 
-use grammar::transformations;
-use grammar::parser::{ parse_from_file, write_json_to_file };
+use grammar::sets::{FirstFollow, GrammarWithSets};
+use grammar::ll1::LL1Table;
+use grammar::codegen::generate_rust_parser;
+use grammar::emit::{write_emitted_to_file, EmitFormat};
+use grammar::lint::{lint, Severity};
+use grammar::Grammar;
+use grammar::cyk::{Cyk, CykTable};
+use grammar::earley::Chart;
+use grammar::parse_tree::TreeFormat;
+use grammar::lr::{build_table, Lr0Automaton, Lr1Automaton, LrComparison, LrError, LrMethod, LrTable};
+use grammar::lr_parser::LrParse;
+use grammar::pda::{Pda, PdaConstruction, PdaVerdict, SearchBounds};
+use grammar::regular::{get_linearity, regex_to_grammar, write_nfa_to_file, Nfa};
+use grammar::parser::{ parse_from_file, write_json_to_file, write_text_to_file, OutputFormat };
+use grammar::pipeline::{self, Stage, DEFAULT_PIPELINE};
 
 #[derive(Debug, StructOpt)]
-#[structopt(name = "cfg2proper", about = "This utility converts CFG to proper CFG")]
+#[structopt(
+    name = "cfg2proper",
+    about = "This utility converts CFG to proper CFG",
+    after_help = "Without a subcommand the arguments are passed to convert, e.g. cfg2proper input_cfg.txt --pipeline e-free,unit,cnf. \
+                  A file named like a subcommand is converted with an explicit convert, e.g. cfg2proper convert lint"
+)]
 struct Opt {
-    /// Input file with CFG in JSON format
+    #[structopt(subcommand)]
+    cmd: Command,
+}
+
+#[derive(Debug, StructOpt)]
+struct ConvertOpt {
+    /// Input file with CFG, .bnf and .ebnf files are read in BNF/EBNF notation,
+    /// .json files in the format of output_cfg.json
     #[structopt(default_value = "input_cfg.txt")]
     filename: String,
+
+    /// Comma separated transformations applied to the grammar in order:
+    /// e-free, useless, unreachable, unit, left-rec, left-factor, cnf, gnf
+    #[structopt(long, use_delimiter = true, default_value = DEFAULT_PIPELINE)]
+    pipeline: Vec<Stage>,
+
+    /// Comma separated order of non-terminals used by left recursion elimination,
+    /// other non-terminals follow in order of their first appearance
+    #[structopt(long, use_delimiter = true)]
+    lr_order: Vec<String>,
+
+    /// Print the grammar after every pipeline stage
+    #[structopt(long)]
+    print_stages: bool,
+
+    /// Print every resulting production with the source production it was
+    /// derived from and the transformations applied to it
+    #[structopt(long)]
+    print_origins: bool,
+
+    /// Output file for the resulting grammar
+    #[structopt(short, long, default_value = "output_cfg.json")]
+    output: String,
+
+    /// Output format: json or text (the count-prefixed input format)
+    #[structopt(long, default_value = "json")]
+    output_format: OutputFormat,
+
+    /// Print FIRST and FOLLOW sets of the resulting grammar and include them in JSON output
+    #[structopt(long)]
+    first_follow: bool,
+
+    /// Build LL(1) parse table of the resulting grammar, report its conflicts
+    /// and write it in JSON format next to the output file with .ll1.json
+    /// extension
+    #[structopt(long)]
+    ll1: bool,
+
+    /// Generate Rust module with recursive-descent parser of the resulting
+    /// grammar to the file, the grammar must be LL(1)
+    #[structopt(long)]
+    rust_parser: Option<String>,
+
+    /// Also write the resulting grammar for external parser generators:
+    /// bison, antlr or w3c-ebnf, next to the output file with .y, .g4 or
+    /// .ebnf extension
+    #[structopt(long, use_delimiter = true)]
+    emit: Vec<EmitFormat>,
 }
 
-fn main() {
-    let opt = Opt::from_args();
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Convert CFG to proper CFG without left recursion, it's the default subcommand
+    Convert(ConvertOpt),
+    /// Check whether sentences belong to the language of the grammar
+    Parse(ParseOpt),
+    /// Build LR parse table of the grammar and report its conflicts
+    Lr(LrOpt),
+    /// Report unproductive, unreachable and unused symbols, cycles, left
+    /// recursion and nullable start symbol, exits with 1 if errors are found
+    Lint(LintOpt),
+    /// Build pushdown automaton of the grammar and check whether sentences
+    /// are accepted by it
+    Pda(PdaOpt),
+    /// Convert right- or left-linear grammar to NFA and DFA, or build
+    /// right-linear grammar from a regex
+    Regular(RegularOpt),
+}
+
+#[derive(Debug, StructOpt)]
+struct RegularOpt {
+    /// Build right-linear grammar from the regex instead of reading it from
+    /// the file, regex syntax is the one of regex2fsm
+    #[structopt(long, conflicts_with = "grammar")]
+    regex: Option<String>,
+
+    /// Output file for the grammar built from the regex in the text format
+    #[structopt(short, long, requires = "regex")]
+    output: Option<String>,
+
+    /// Write the NFA to the file in the automaton format of regex2fsm
+    #[structopt(long)]
+    nfa: Option<String>,
+
+    /// Write the DFA in JSON format with the fields of regex2fsm Dfa to the file
+    #[structopt(long)]
+    json: Option<String>,
+
+    /// Render the DFA in dot format to the file
+    #[structopt(long)]
+    dot: Option<String>,
+
+    /// Input file with CFG
+    #[structopt(required_unless = "regex")]
+    grammar: Option<String>,
+}
+
+#[derive(Debug, StructOpt)]
+struct PdaOpt {
+    /// Construction of the automaton: top-down (single state, accepts by
+    /// empty stack) or bottom-up (shift-reduce, accepts by final state)
+    #[structopt(short, long, default_value = "top-down")]
+    construction: PdaConstruction,
+
+    /// Render the automaton in dot format to the file
+    #[structopt(long)]
+    dot: Option<String>,
+
+    /// Print configurations of the accepting run
+    #[structopt(long)]
+    trace: bool,
+
+    /// Maximum number of configurations explored for a sentence
+    #[structopt(long, default_value = "100000")]
+    max_configurations: usize,
+
+    /// Maximum stack height of explored configurations
+    #[structopt(long, default_value = "64")]
+    max_stack: usize,
+
+    /// Input file with CFG
+    grammar: String,
+
+    /// Sentence of whitespace separated terminals, sentences are read
+    /// from stdin line by line if it's omitted
+    sentence: Option<String>,
+}
+
+#[derive(Debug, StructOpt)]
+struct LintOpt {
+    /// Input file with CFG
+    grammar: String,
+}
+
+#[derive(Debug, StructOpt)]
+struct LrOpt {
+    /// Table construction method: lr0, slr1, lalr1 or lr1
+    #[structopt(short, long, default_value = "slr1")]
+    method: LrMethod,
+
+    /// Report conflicts of SLR(1), LALR(1) and LR(1) tables instead of
+    /// building a single table
+    #[structopt(long, conflicts_with_all = &["method", "dot", "json"])]
+    compare: bool,
+
+    /// Render item automaton of the method in dot format to the file
+    #[structopt(long)]
+    dot: Option<String>,
+
+    /// Write the parse table in JSON format to the file
+    #[structopt(long)]
+    json: Option<String>,
+
+    /// Input file with CFG
+    grammar: String,
+}
+
+#[derive(Debug, StructOpt)]
+struct ParseOpt {
+    /// Run CYK algorithm over Chomsky normal form of the grammar
+    #[structopt(long, required_unless_one = &["earley", "lr"], conflicts_with_all = &["earley", "lr"])]
+    cyk: bool,
+
+    /// Run Earley algorithm over the grammar as is
+    #[structopt(long, conflicts_with = "lr")]
+    earley: bool,
+
+    /// Run shift-reduce parser over the table built with the method:
+    /// lr0, slr1, lalr1 or lr1
+    #[structopt(long)]
+    lr: Option<LrMethod>,
+
+    /// Print stack, input and action of every shift-reduce parser step
+    #[structopt(long, requires = "lr")]
+    trace: bool,
+
+    /// Print Earley chart of every sentence
+    #[structopt(long, requires = "earley")]
+    chart: bool,
+
+    /// Print all parse trees instead of the first one
+    #[structopt(long)]
+    all: bool,
+
+    /// Parse tree output format: text or dot
+    #[structopt(short, long, default_value = "text")]
+    format: TreeFormat,
+
+    /// Input file with CFG
+    grammar: String,
+
+    /// Sentence of whitespace separated terminals, sentences are read
+    /// from stdin line by line if it's omitted
+    sentence: Option<String>,
+}
+
+fn run_lr(opt: &LrOpt) {
+    let g = match parse_from_file(&opt.grammar) {
+        Ok(v) => v,
+        Err(e) => {
+            panic!("Failed to parse cfg from file: {}", e);
+        }
+    };
+
+    if opt.compare {
+        print!("{}", LrComparison::new(&g));
+        return;
+    }
+
+    if let Some(output) = &opt.dot {
+        match opt.method {
+            LrMethod::Lr0 | LrMethod::Slr1 => Lr0Automaton::new(&g).render_to(output),
+            LrMethod::Lalr1 => Lr1Automaton::lalr(&g).render_to(output),
+            LrMethod::Lr1 => Lr1Automaton::new(&g).render_to(output),
+        }
+    }
+
+    let table = build_table(&g, opt.method);
+
+    if let Some(output) = &opt.json {
+        write_json_to_file(&table, output).unwrap();
+    }
+
+    println!("{} parse table:\n{}", opt.method, table);
+}
+
+fn run_pda(opt: &PdaOpt) {
+    let g = match parse_from_file(&opt.grammar) {
+        Ok(v) => v,
+        Err(e) => {
+            panic!("Failed to parse cfg from file: {}", e);
+        }
+    };
+
+    let pda = Pda::new(&g, opt.construction);
+    print!("{} PDA:\n{}", opt.construction, pda);
+
+    if let Some(output) = &opt.dot {
+        pda.render_to(output);
+    }
+
+    let bounds = SearchBounds { max_configurations: opt.max_configurations, max_stack: opt.max_stack };
+    let run_sentence = |sentence: &str| {
+        let tokens: Vec<String> = sentence.split_whitespace().map(|v| v.to_string()).collect();
+        let run = pda.run(&tokens, bounds);
+
+        println!("Sentence is {} ({} configuration(s) explored)", run.verdict, run.explored);
+        if opt.trace && run.verdict == PdaVerdict::Accepted {
+            print!("Accepting run:\n{}", run);
+        }
+    };
+
+    match &opt.sentence {
+        Some(sentence) => run_sentence(sentence),
+        _ => {
+            for line in io::stdin().lock().lines() {
+                let line = line.expect("Failed to read sentence from stdin");
+                println!("> {}", line);
+                run_sentence(&line);
+            }
+        }
+    }
+}
+
+fn run_regular(opt: &RegularOpt) {
+    let g = match (&opt.regex, &opt.grammar) {
+        (Some(regex), _) => match regex_to_grammar(regex) {
+            Ok(v) => v,
+            Err(e) => {
+                panic!("Failed to build grammar from regex: {}", e);
+            }
+        },
+        (_, Some(filename)) => match parse_from_file(filename) {
+            Ok(v) => v,
+            Err(e) => {
+                panic!("Failed to parse cfg from file: {}", e);
+            }
+        },
+        _ => unreachable!(),
+    };
+
+    let linearity = match get_linearity(&g) {
+        Some(v) => v,
+        None => {
+            println!("Grammar is neither right- nor left-linear, it is not converted to finite automata");
+            return;
+        }
+    };
+    println!("Grammar is {}: {}", linearity, g);
+
+    if let Some(output) = &opt.output {
+        if let Err(e) = write_text_to_file(&g, output) {
+            panic!("Failed to write grammar to {}: {}", output, e);
+        }
+    }
+
+    let nfa = Nfa::from_grammar(&g).unwrap();
+    print!("NFA:\n{}", nfa);
+
+    if let Some(output) = &opt.nfa {
+        if let Err(e) = write_nfa_to_file(&nfa, output) {
+            panic!("Failed to write NFA to {}: {}", output, e);
+        }
+    }
+
+    let dfa = nfa.determinize();
+    print!("DFA:\n{}", dfa);
+
+    if let Some(output) = &opt.json {
+        write_json_to_file(&dfa, output).unwrap();
+    }
+
+    if let Some(output) = &opt.dot {
+        dfa.render_to(output);
+    }
+}
+
+fn run_lint(opt: &LintOpt) {
+    let g = match parse_from_file(&opt.grammar) {
+        Ok(v) => v,
+        Err(e) => {
+            panic!("Failed to parse cfg from file: {}", e);
+        }
+    };
+
+    let diagnostics = lint(&g);
+    for diagnostic in diagnostics.iter() {
+        println!("{}", diagnostic);
+    }
+
+    let count = |severity: Severity| diagnostics.iter().filter(|v| v.severity == severity).count();
+    println!("{} error(s), {} warning(s)", count(Severity::Error), count(Severity::Warning));
+
+    if count(Severity::Error) > 0 {
+        std::process::exit(1);
+    }
+}
+
+fn run_parse(opt: &ParseOpt) {
+    let g = match parse_from_file(&opt.grammar) {
+        Ok(v) => v,
+        Err(e) => {
+            panic!("Failed to parse cfg from file: {}", e);
+        }
+    };
+
+    let lr_table = opt.lr.map(|method| build_table(&g, method));
+    let cyk = match opt.cyk {
+        true => Some(Cyk::new(&g)),
+        _ => None,
+    };
+
+    if let Some(table) = &lr_table {
+        let conflicts = table.conflicts();
+        if !conflicts.is_empty() {
+            println!("{}", LrError::Conflicts(table.method, conflicts));
+            return;
+        }
+    }
+
+    match &opt.sentence {
+        Some(sentence) => parse_sentence(&g, sentence, cyk.as_ref(), lr_table.as_ref(), opt),
+        _ => {
+            for line in io::stdin().lock().lines() {
+                let line = line.expect("Failed to read sentence from stdin");
+                println!("> {}", line);
+                parse_sentence(&g, &line, cyk.as_ref(), lr_table.as_ref(), opt);
+            }
+        }
+    }
+}
+
+fn parse_sentence(g: &Grammar, sentence: &str, cyk: Option<&Cyk>, lr_table: Option<&LrTable>, opt: &ParseOpt) {
+    let tokens: Vec<String> = sentence.split_whitespace().map(|v| v.to_string()).collect();
+
+    if let Some(token) = tokens.iter().find(|v| !g.terms.contains(*v)) {
+        println!("Sentence is rejected: {} is not a terminal of the grammar", token);
+        return;
+    }
+
+    let limit = if opt.all { None } else { Some(1) };
+
+    let (count, trees) = match (cyk, opt.earley, lr_table) {
+        (Some(cyk), _, _) => {
+            let table = CykTable::new(cyk, &tokens);
+
+            if !table.accepts() {
+                println!("Sentence is rejected");
+                return;
+            }
+
+            (table.count_trees(), table.trees(limit))
+        }
+        (_, true, _) => {
+            let chart = Chart::new(g, &tokens);
+
+            if opt.chart {
+                print!("Earley chart:\n{}", chart);
+            }
+
+            match chart.sppf() {
+                Some(sppf) => (sppf.count_trees(), sppf.trees(limit)),
+                _ => {
+                    println!("Sentence is rejected");
+                    return;
+                }
+            }
+        }
+        (_, _, Some(table)) => {
+            let parse = match LrParse::new(table, &tokens) {
+                Ok(v) => v,
+                Err(e) => {
+                    println!("{}", e);
+                    return;
+                }
+            };
 
+            if opt.trace {
+                print!("{} parser trace:\n{}", table.method, parse);
+            }
+
+            match parse.result {
+                Ok(tree) => (Some(1), vec![tree]),
+                Err(e) => {
+                    println!("Sentence is rejected: {}", e);
+                    return;
+                }
+            }
+        }
+        _ => unreachable!("Parsing algorithm must be chosen"),
+    };
+
+    let count = match count {
+        Some(v) => v.to_string(),
+        _ => String::from("infinitely many"),
+    };
+    println!("Sentence is accepted, parse trees: {}", count);
+
+    for (i, tree) in trees.iter().enumerate() {
+        debug_assert_eq!(tree.yield_terms(), tokens, "Parse tree must derive the sentence");
+
+        if opt.format == TreeFormat::Text {
+            println!("Parse tree {}:", i + 1);
+        }
+        print!("{}", tree.render(opt.format));
+    }
+}
+
+fn run_convert(opt: &ConvertOpt) {
     let g = parse_from_file(&opt.filename);
     let g = match g {
         Ok(v) => v,
@@ -28,20 +500,131 @@ fn main() {
         }
     };
 
-    let gm1 = transformations::to_e_free(&g);
+    let stages = &opt.pipeline;
+
+    if let Some(non_term) = opt.lr_order.iter().find(|v| !g.non_terms.contains(*v)) {
+        panic!("Unknown non-terminal {} in left recursion elimination order", non_term);
+    }
+
+    let result = pipeline::run(&g, stages, &opt.lr_order, |stage, g| {
+        if opt.print_stages {
+            println!("After {}: {}\n", stage, g);
+        }
+    });
+    let result = match result {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Failed to transform grammar: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let stage_names: Vec<String> = stages.iter().map(|stage| stage.to_string()).collect();
+    println!("Result of {} pipeline: {}", stage_names.join(", "), result);
+
+    if opt.print_origins {
+        println!("Origins of productions:");
+        for prod in result.productions.iter() {
+            println!("{} ({})", prod, prod.origin);
+        }
+    }
 
-    let gm2 = transformations::remove_useless_symbols(&gm1);
+    if opt.ll1 {
+        let table = LL1Table::new(&result, &FirstFollow::new(&result));
 
-    let gm3 = transformations::remove_unit_productions(&gm2);
+        println!("LL(1) parse table:\n{}", table);
 
-    let gm4 = transformations::eliminate_indirect_lr(&gm3);
+        let filename = std::path::Path::new(&opt.output).with_extension("ll1.json");
+        let filename = filename.to_string_lossy();
 
-    println!("Proper cfg grammar without left recursion: {}", gm4);
+        match write_json_to_file(&table, &filename) {
+            Ok(_) => println!("LL(1) table is written to {}", filename),
+            Err(e) => {
+                panic!("Failed to write LL(1) table in json format to {}: {}", filename, e);
+            }
+        };
+    }
 
-    match write_json_to_file(&gm4, "output_cfg.json") {
+    if let Some(output) = &opt.rust_parser {
+        let table = LL1Table::new(&result, &FirstFollow::new(&result));
+
+        if table.is_ll1 {
+            match std::fs::write(output, generate_rust_parser(&result, &table)) {
+                Ok(_) => println!("Rust parser is written to {}", output),
+                Err(e) => panic!("Failed to write Rust parser to {}: {}", output, e),
+            }
+        } else {
+            println!("Grammar is not LL(1), Rust parser is not generated, conflicts are reported by --ll1");
+        }
+    }
+
+    for format in opt.emit.iter() {
+        let filename = std::path::Path::new(&opt.output).with_extension(format.extension());
+        let filename = filename.to_string_lossy();
+
+        match write_emitted_to_file(&result, *format, &filename) {
+            Ok(_) => println!("Grammar in {} format is written to {}", format, filename),
+            Err(e) => panic!("Failed to write grammar in {} format to {}: {}", format, filename, e),
+        }
+    }
+
+    let written = match (opt.output_format, opt.first_follow) {
+        (OutputFormat::Json, true) => {
+            let sets = FirstFollow::new(&result);
+
+            println!("FIRST and FOLLOW sets:\n{}", sets);
+
+            write_json_to_file(&GrammarWithSets { grammar: &result, sets: &sets }, &opt.output)
+        }
+        (OutputFormat::Json, _) => write_json_to_file(&result, &opt.output),
+        (OutputFormat::Text, first_follow) => {
+            if first_follow {
+                println!("FIRST and FOLLOW sets:\n{}", FirstFollow::new(&result));
+            }
+
+            write_text_to_file(&result, &opt.output)
+        }
+    };
+
+    match written {
         Ok(_) => (),
         Err(e) => {
-            panic!("Failed to write grammar in json format");
+            panic!("Failed to write grammar to {}: {}", opt.output, e);
         }
     };
 }
+
+// Inserts convert subcommand unless the first argument names a subcommand
+// or asks for help, so `cfg2proper input_cfg.txt` keeps working even if the
+// file name resembles a subcommand
+fn args_with_default_command() -> Vec<OsString> {
+    let mut args: Vec<OsString> = env::args_os().collect();
+
+    // Names of the subcommands, other first arguments are passed to convert.
+    // Clap adds help subcommand only when parsing, so it's not among them
+    let app = Opt::clap();
+    let commands: Vec<&str> = app.p.subcommands.iter().map(|v| v.get_name()).chain(iter::once("help")).collect();
+
+    let explicit = match args.get(1).and_then(|v| v.to_str()) {
+        Some(arg) => commands.contains(&arg) || ["-h", "--help", "-V", "--version"].contains(&arg),
+        _ => false,
+    };
+    if !explicit {
+        args.insert(1, OsString::from("convert"));
+    }
+
+    args
+}
+
+fn main() {
+    let opt = Opt::from_iter(args_with_default_command());
+
+    match &opt.cmd {
+        Command::Convert(convert_opt) => run_convert(convert_opt),
+        Command::Parse(parse_opt) => run_parse(parse_opt),
+        Command::Lr(lr_opt) => run_lr(lr_opt),
+        Command::Lint(lint_opt) => run_lint(lint_opt),
+        Command::Pda(pda_opt) => run_pda(pda_opt),
+        Command::Regular(regular_opt) => run_regular(regular_opt),
+    }
+}
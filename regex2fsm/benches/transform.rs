@@ -0,0 +1,107 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use regex2fsm::fsm::dfa::{calc_positions, construct, legacy_construct, transform, Positions};
+use regex2fsm::fsm::Dfa;
+use regex2fsm::syntax_tree::parser::parse;
+
+use std::time::{Duration, Instant};
+
+const LETTERS: &str = "(a|b|c|d|e|f|g|h|i|j|k|l|m|n|o|p|q|r|s|t|u|v|w|x|y|z)";
+const DIGITS: &str = "(0|1|2|3|4|5|6|7|8|9)";
+
+// Runs of every construction timed for the speedup report
+const SPEEDUP_SAMPLES: usize = 25;
+
+fn realistic_regexes() -> Vec<(&'static str, String)> {
+    let identifier = format!("({}({}|{}|_)*)", LETTERS, LETTERS, DIGITS);
+    let number = format!("({}{}*(\\.{}{}*)*)", DIGITS, DIGITS, DIGITS, DIGITS);
+    let keywords = String::from("(if|else|while|for|return|break|continue|fn|let|match|loop|struct|enum|impl)");
+    let tokens = format!("({}|{}|{}|\\+|-|\\*|/|=|==|\\(|\\))", keywords, identifier, number);
+
+    vec![
+        ("identifier", identifier),
+        ("number", number),
+        ("keywords", keywords),
+        ("tokens", tokens),
+    ]
+}
+
+// Regexes with exponential number of DFA states or lots of positions
+fn pathological_regexes() -> Vec<(&'static str, String)> {
+    let nth_from_end = |n: usize| format!("((a|b)*a{})", "(a|b)".repeat(n));
+    let nested_stars = format!("({})", "(a*b*)*".repeat(16));
+
+    vec![
+        ("nth_from_end_6", nth_from_end(6)),
+        ("nth_from_end_8", nth_from_end(8)),
+        ("nested_stars", nested_stars),
+    ]
+}
+
+// Median time of the construction, measured apart from criterion
+// to report legacy/bitset ratio of every regex
+fn median_time(positions: &Positions, construct: fn(&Positions) -> Dfa) -> Duration {
+    let mut times: Vec<Duration> = (0..SPEEDUP_SAMPLES)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(construct(positions));
+            start.elapsed()
+        })
+        .collect();
+    times.sort();
+
+    times[times.len() / 2]
+}
+
+fn bench_construction(c: &mut Criterion, group_name: &str, regexes: Vec<(&'static str, String)>) {
+    let mut group = c.benchmark_group(group_name);
+    group.sample_size(20);
+
+    let mut speedups: Vec<(String, f64)> = vec![];
+
+    for (name, regex) in regexes.iter() {
+        let positions = calc_positions(parse(regex).unwrap());
+
+        let id = format!("{} ({} states)", name, construct(&positions).states.len());
+
+        group.bench_with_input(BenchmarkId::new("legacy", &id), &positions, |b, positions| {
+            b.iter(|| legacy_construct(positions))
+        });
+        group.bench_with_input(BenchmarkId::new("bitset", &id), &positions, |b, positions| {
+            b.iter(|| construct(positions))
+        });
+
+        let legacy = median_time(&positions, legacy_construct);
+        let bitset = median_time(&positions, construct);
+        speedups.push((id, legacy.as_secs_f64() / bitset.as_secs_f64()));
+    }
+
+    group.finish();
+
+    for (id, speedup) in speedups.iter() {
+        println!("{}/{}: bitset is {:.1}x faster than legacy", group_name, id, speedup);
+    }
+}
+
+fn realistic(c: &mut Criterion) {
+    bench_construction(c, "realistic", realistic_regexes());
+}
+
+fn pathological(c: &mut Criterion) {
+    bench_construction(c, "pathological", pathological_regexes());
+}
+
+// Whole pipeline from regex string to DFA
+fn end_to_end(c: &mut Criterion) {
+    let mut group = c.benchmark_group("end_to_end");
+
+    for (name, regex) in realistic_regexes().iter() {
+        group.bench_with_input(BenchmarkId::from_parameter(name), regex, |b, regex| {
+            b.iter(|| transform(parse(regex).unwrap()))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, realistic, pathological, end_to_end);
+criterion_main!(benches);
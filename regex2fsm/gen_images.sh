@@ -9,3 +9,9 @@ if [[ -e $dfa.dot ]] && [[ -e $min_dfa.dot ]]; then
 else
     echo "One of the $dfa and $min_dfa files not exist."
 fi
+
+for transducer in mealy min_mealy moore; do
+    if [[ -e $transducer.dot ]]; then
+        dot -Tpng $transducer.dot -o $transducer.png
+    fi
+done
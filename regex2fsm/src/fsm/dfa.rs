@@ -1,8 +1,26 @@
 use super::{Dfa, NodeWrapper, SyntaxTree};
 
+use fixedbitset::FixedBitSet;
 use std::collections::{HashMap, HashSet};
 
+// Positions of syntax tree leaves which are needed for subset construction
+#[derive(Debug)]
+pub struct Positions {
+    pub alphabet: Vec<String>,
+    // Alphabet index of every position, terminator has none
+    pub symbols: Vec<Option<usize>>,
+    pub first_pos: Vec<usize>,
+    pub follow_pos: Vec<Vec<usize>>,
+    pub terminator: usize,
+}
+
 pub fn transform(root: Box<SyntaxTree>) -> Dfa {
+    let positions = calc_positions(root);
+
+    construct(&positions)
+}
+
+pub fn calc_positions(root: Box<SyntaxTree>) -> Positions {
     let mut wrapper = NodeWrapper::new(&root);
 
     let (alphabet, leaf_chars) = wrapper.numerate_leaves();
@@ -15,14 +33,108 @@ pub fn transform(root: Box<SyntaxTree>) -> Dfa {
     #[cfg(debug_assertions)]
     println!("follow pos : {:#?}", follow_pos);
 
-    let mut dfa = Dfa {
+    let symbols = (0..follow_pos.len())
+        .map(|i| {
+            leaf_chars
+                .get(&i)
+                .map(|c| alphabet.iter().position(|v| v == c).unwrap())
+        })
+        .collect();
+
+    Positions {
         alphabet,
+        symbols,
+        first_pos: wrapper.first_pos.clone(),
+        follow_pos,
+        terminator: leaf_chars.len(),
+    }
+}
+
+// Subset construction over bitset represented position sets. Every new set
+// is looked up in a hash index instead of comparing it with all existing states
+pub fn construct(positions: &Positions) -> Dfa {
+    let size = positions.follow_pos.len();
+
+    let follow_pos: Vec<FixedBitSet> = positions
+        .follow_pos
+        .iter()
+        .map(|v| to_bitset(v, size))
+        .collect();
+
+    // Positions labeled by every alphabet symbol
+    let mut symbol_pos = vec![FixedBitSet::with_capacity(size); positions.alphabet.len()];
+    for (i, symbol) in positions.symbols.iter().enumerate() {
+        if let Some(col) = symbol {
+            symbol_pos[*col].insert(i);
+        }
+    }
+
+    let mut dfa = Dfa {
+        alphabet: positions.alphabet.clone(),
+        states: vec![],
+        trans: vec![],
+        is_terminal: HashSet::new(),
+    };
+
+    let mut sets: Vec<FixedBitSet> = vec![];
+    let mut index: HashMap<FixedBitSet, usize> = HashMap::new();
+
+    let first_state = to_bitset(&positions.first_pos, size);
+    dfa.add_state(&first_state.ones().collect(), first_state.contains(positions.terminator));
+    index.insert(first_state.clone(), 0);
+    sets.push(first_state);
+
+    let mut row = 0;
+
+    while row < sets.len() {
+        for (col, symbol_pos) in symbol_pos.iter().enumerate() {
+            let mut new_state = FixedBitSet::with_capacity(size);
+            for pos in sets[row].intersection(symbol_pos) {
+                new_state.union_with(&follow_pos[pos]);
+            }
+
+            if new_state.count_ones(..) == 0 {
+                continue;
+            }
+
+            let new_row = match index.get(&new_state) {
+                Some(v) => *v,
+                _ => {
+                    dfa.add_state(&new_state.ones().collect(), new_state.contains(positions.terminator));
+                    index.insert(new_state.clone(), sets.len());
+                    sets.push(new_state);
+                    sets.len() - 1
+                }
+            };
+
+            dfa.trans[row][col] = Some(new_row);
+        }
+
+        row += 1;
+    }
+
+    dfa
+}
+
+// Subset construction as it was before bitset redesign: every lookup clones
+// and sorts all existing states, every new state is re-sorted in the inner loop.
+// It's kept as a reference for benchmarks and tests of `construct`
+pub fn legacy_construct(positions: &Positions) -> Dfa {
+    let leaf_chars: HashMap<usize, String> = positions
+        .symbols
+        .iter()
+        .enumerate()
+        .filter_map(|(i, v)| v.map(|col| (i, positions.alphabet[col].clone())))
+        .collect();
+
+    let mut dfa = Dfa {
+        alphabet: positions.alphabet.clone(),
         states: vec![],
         trans: vec![],
         is_terminal: HashSet::new(),
     };
 
-    dfa.add_state(&wrapper.first_pos.clone(), wrapper.first_pos.contains(&leaf_chars.len()));
+    legacy_add_state(&mut dfa, &positions.first_pos, positions.first_pos.contains(&positions.terminator));
 
     let mut row = 0;
 
@@ -30,9 +142,18 @@ pub fn transform(root: Box<SyntaxTree>) -> Dfa {
         for col in 0..dfa.alphabet.len() {
             let curr_char = &dfa.alphabet[col];
             let curr_state = &dfa.states[row];
-            let (new_state, is_terminal) =
-                form_state(curr_char, curr_state, &follow_pos, &leaf_chars);
-            match match_state(&dfa.states, &new_state) {
+
+            let mut new_state = vec![];
+            for state_value in curr_state.iter() {
+                if leaf_chars.get(state_value) == Some(curr_char) {
+                    new_state.extend_from_slice(&positions.follow_pos[*state_value]);
+                    new_state.sort();
+                    new_state.dedup();
+                }
+            }
+            let is_terminal = new_state.contains(&positions.terminator);
+
+            match legacy_match_state(&dfa.states, &new_state) {
                 Some(v) => {
                     dfa.trans[row][col] = Some(v);
                 }
@@ -40,7 +161,7 @@ pub fn transform(root: Box<SyntaxTree>) -> Dfa {
                     if new_state.is_empty() {
                         continue;
                     }
-                    dfa.add_state(&new_state, is_terminal);
+                    legacy_add_state(&mut dfa, &new_state, is_terminal);
                     dfa.trans[row][col] = Some(dfa.states.len() - 1);
                 }
             }
@@ -52,46 +173,61 @@ pub fn transform(root: Box<SyntaxTree>) -> Dfa {
     dfa
 }
 
-fn form_state(
-    curr_char: &str,
-    curr_state: &Vec<usize>,
-    follow_pos: &Vec<Vec<usize>>,
-    leaf_chars: &HashMap<usize, String>,
-) -> (Vec<usize>, bool) {
-    let mut result = vec![];
-
-    for state_value in curr_state.iter() {
-        if leaf_chars
-            .get(state_value)
-            .map_or(false, |v| v == curr_char)
-        {
-            result.extend_from_slice(&follow_pos[*state_value as usize]);
-            result.sort();
-            result.dedup();
-        }
-    }
-
-    let mut is_terminal = false;
+fn legacy_add_state(dfa: &mut Dfa, new_state: &[usize], is_terminal: bool) {
+    dfa.states.push(new_state.to_vec());
+    dfa.trans.push(vec![None; dfa.alphabet.len()]);
 
-    if result.contains(&leaf_chars.len()) {
-        is_terminal = true;
+    if is_terminal {
+        dfa.is_terminal.insert(dfa.states.len() - 1);
     }
-
-    (result, is_terminal)
 }
 
-fn match_state(states: &Vec<Vec<usize>>, new_state: &Vec<usize>) -> Option<usize> {
-    let mut b_vec = new_state.clone();
+fn legacy_match_state(states: &[Vec<usize>], new_state: &[usize]) -> Option<usize> {
+    let mut b_vec = new_state.to_vec();
     b_vec.sort();
 
     for (i, state) in states.iter().enumerate() {
         let mut a_vec = state.clone();
         a_vec.sort();
 
-        if a_vec.len() == b_vec.len() && a_vec.iter().zip(&b_vec).all(|(a, b)| a == b) {
+        if a_vec == b_vec {
             return Some(i);
         }
     }
 
     None
 }
+
+fn to_bitset(positions: &[usize], size: usize) -> FixedBitSet {
+    let mut set = FixedBitSet::with_capacity(size);
+    set.extend(positions.iter().cloned());
+
+    set
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{calc_positions, construct, legacy_construct};
+    use crate::syntax_tree::parser;
+
+    #[test]
+    fn construct_matches_legacy_construct() {
+        let regexes = ["a", "(a|b)*abb", "(a|b)*a(a|b)(a|b)(a|b)", "(a*b*)*(a*b*)*", "(if|else|i(a|b)*)", "a\\*(b|\\()"];
+
+        for regex in regexes.iter() {
+            let positions = calc_positions(parser::parse(&regex.to_string()).unwrap());
+
+            let dfa = construct(&positions);
+            let legacy = legacy_construct(&positions);
+
+            assert_eq!(dfa.alphabet, legacy.alphabet, "{}", regex);
+            assert_eq!(dfa.trans, legacy.trans, "{}", regex);
+            assert_eq!(dfa.is_terminal, legacy.is_terminal, "{}", regex);
+            for (state, legacy_state) in dfa.states.iter().zip(legacy.states.iter()) {
+                let mut legacy_state = legacy_state.clone();
+                legacy_state.sort_unstable();
+                assert_eq!(state, &legacy_state, "{}", regex);
+            }
+        }
+    }
+}
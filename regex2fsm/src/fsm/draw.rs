@@ -1,3 +1,4 @@
+use super::transducer::{Mealy, Moore};
 use super::Dfa;
 
 use std::borrow::Cow;
@@ -14,11 +15,11 @@ impl<'a> dot::Labeller<'a, Nd, Ed> for Dfa {
         dot::Id::new(format!("N{}", n)).unwrap()
     }
 
-    fn node_label(&self, n: &Nd) -> dot::LabelText {
+    fn node_label(&self, n: &Nd) -> dot::LabelText<'_> {
         dot::LabelText::LabelStr(format!("{}", n).into())
     }
 
-    fn edge_label(&self, e: &Ed) -> dot::LabelText {
+    fn edge_label(&self, e: &Ed) -> dot::LabelText<'_> {
         dot::LabelText::LabelStr(e.2.clone().into())
     }
 
@@ -54,3 +55,101 @@ impl<'a> dot::GraphWalk<'a, Nd, Ed> for Dfa {
 
     fn target(&self, e: &Ed) -> Nd {e.1}
 }
+
+impl<'a> dot::Labeller<'a, Nd, Ed> for Mealy {
+    fn graph_id(&self) -> dot::Id<'a> {
+        dot::Id::new("mealy").unwrap()
+    }
+
+    fn node_id(&self, n: &Nd) -> dot::Id<'a> {
+        dot::Id::new(format!("N{}", n)).unwrap()
+    }
+
+    fn node_label(&self, n: &Nd) -> dot::LabelText<'_> {
+        dot::LabelText::LabelStr(format!("{}", n).into())
+    }
+
+    fn edge_label(&self, e: &Ed) -> dot::LabelText<'_> {
+        dot::LabelText::LabelStr(e.2.clone().into())
+    }
+
+    fn node_style(&self, n: &Nd) -> dot::Style {
+        match self.is_terminal.contains(n) {
+            true => dot::Style::Bold,
+            _ => dot::Style::Solid
+        }
+    }
+}
+
+impl<'a> dot::GraphWalk<'a, Nd, Ed> for Mealy {
+    fn nodes(&self) -> dot::Nodes<'a, Nd> {
+        (0..self.trans.len()).collect()
+    }
+
+    fn edges(&self) -> dot::Edges<'a, Ed> {
+        let mut edges: Vec<Ed> = vec!();
+
+        self.trans.iter().enumerate().for_each(|(state, to_states)| {
+            to_states.iter().enumerate().for_each(|(c, to_state)| {
+                if let Some((to_state, output)) = to_state {
+                    edges.push((state, *to_state, format!("{}/{}", self.inputs[c], output)))
+                }
+            })
+        });
+
+        Cow::Owned(edges)
+    }
+
+    fn source(&self, e: &Ed) -> Nd {e.0}
+
+    fn target(&self, e: &Ed) -> Nd {e.1}
+}
+
+impl<'a> dot::Labeller<'a, Nd, Ed> for Moore {
+    fn graph_id(&self) -> dot::Id<'a> {
+        dot::Id::new("moore").unwrap()
+    }
+
+    fn node_id(&self, n: &Nd) -> dot::Id<'a> {
+        dot::Id::new(format!("N{}", n)).unwrap()
+    }
+
+    fn node_label(&self, n: &Nd) -> dot::LabelText<'_> {
+        dot::LabelText::LabelStr(format!("{}/{}", n, self.outputs[*n]).into())
+    }
+
+    fn edge_label(&self, e: &Ed) -> dot::LabelText<'_> {
+        dot::LabelText::LabelStr(e.2.clone().into())
+    }
+
+    fn node_style(&self, n: &Nd) -> dot::Style {
+        match self.is_terminal.contains(n) {
+            true => dot::Style::Bold,
+            _ => dot::Style::Solid
+        }
+    }
+}
+
+impl<'a> dot::GraphWalk<'a, Nd, Ed> for Moore {
+    fn nodes(&self) -> dot::Nodes<'a, Nd> {
+        (0..self.trans.len()).collect()
+    }
+
+    fn edges(&self) -> dot::Edges<'a, Ed> {
+        let mut edges: Vec<Ed> = vec!();
+
+        self.trans.iter().enumerate().for_each(|(state, to_states)| {
+            to_states.iter().enumerate().for_each(|(c, to_state)| {
+                if let Some(to_state) = to_state {
+                    edges.push((state, *to_state, self.inputs[c].clone()))
+                }
+            })
+        });
+
+        Cow::Owned(edges)
+    }
+
+    fn source(&self, e: &Ed) -> Nd {e.0}
+
+    fn target(&self, e: &Ed) -> Nd {e.1}
+}
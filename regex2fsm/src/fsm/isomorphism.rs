@@ -0,0 +1,164 @@
+use super::Dfa;
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+impl Dfa {
+    // Renumbers states in BFS order from the start state, visiting
+    // transitions in alphabet order. Unreachable states are dropped
+    pub fn canonical(&self) -> Dfa {
+        let mut columns: Vec<usize> = (0..self.alphabet.len()).collect();
+        columns.sort_by(|a, b| self.alphabet[*a].cmp(&self.alphabet[*b]));
+
+        let mut new_dfa = Dfa {
+            alphabet: columns.iter().map(|c| self.alphabet[*c].clone()).collect(),
+            states: vec![],
+            trans: vec![],
+            is_terminal: HashSet::new(),
+        };
+
+        if self.states.is_empty() {
+            return new_dfa;
+        }
+
+        let mut numbers: HashMap<usize, usize> = HashMap::new();
+        let mut queue = VecDeque::<usize>::new();
+
+        numbers.insert(0, 0);
+        new_dfa.add_state(&self.states[0], self.is_terminal.contains(&0));
+        queue.push_back(0);
+
+        while let Some(state) = queue.pop_front() {
+            let row = numbers[&state];
+
+            for (col, c) in columns.iter().enumerate() {
+                let to_state = match self.trans[state][*c] {
+                    Some(v) => v,
+                    _ => continue,
+                };
+
+                let to_row = match numbers.get(&to_state) {
+                    Some(v) => *v,
+                    _ => {
+                        new_dfa.add_state(&self.states[to_state], self.is_terminal.contains(&to_state));
+                        numbers.insert(to_state, new_dfa.states.len() - 1);
+                        queue.push_back(to_state);
+                        new_dfa.states.len() - 1
+                    }
+                };
+
+                new_dfa.trans[row][col] = Some(to_row);
+            }
+        }
+
+        new_dfa
+    }
+}
+
+// Returns mapping of states of `a` to states of `b` if automata are
+// structurally identical. States unreachable from the start can't be
+// mapped, so automata with them are compared as not identical
+pub fn find_isomorphism(a: &Dfa, b: &Dfa) -> Option<HashMap<usize, usize>> {
+    let mut a_alphabet = a.alphabet.clone();
+    let mut b_alphabet = b.alphabet.clone();
+    a_alphabet.sort();
+    b_alphabet.sort();
+
+    if a_alphabet != b_alphabet {
+        return None;
+    }
+
+    // Column of every `a` symbol within `b`
+    let columns: Vec<usize> = a
+        .alphabet
+        .iter()
+        .map(|c| b.alphabet.iter().position(|v| v == c).unwrap())
+        .collect();
+
+    let mut mapping: HashMap<usize, usize> = HashMap::new();
+    let mut reverse_mapping: HashMap<usize, usize> = HashMap::new();
+    let mut queue = VecDeque::<(usize, usize)>::new();
+
+    if a.states.is_empty() || b.states.is_empty() {
+        return match a.states.len() == b.states.len() {
+            true => Some(mapping),
+            _ => None,
+        };
+    }
+
+    mapping.insert(0, 0);
+    reverse_mapping.insert(0, 0);
+    queue.push_back((0, 0));
+
+    while let Some((u, v)) = queue.pop_front() {
+        if a.is_terminal.contains(&u) != b.is_terminal.contains(&v) {
+            return None;
+        }
+
+        for (c, b_c) in columns.iter().enumerate() {
+            match (a.trans[u][c], b.trans[v][*b_c]) {
+                (Some(to_u), Some(to_v)) => {
+                    match (mapping.get(&to_u), reverse_mapping.get(&to_v)) {
+                        (Some(mapped_v), Some(mapped_u)) if *mapped_v == to_v && *mapped_u == to_u => (),
+                        (None, None) => {
+                            mapping.insert(to_u, to_v);
+                            reverse_mapping.insert(to_v, to_u);
+                            queue.push_back((to_u, to_v));
+                        }
+                        _ => return None,
+                    }
+                }
+                (None, None) => (),
+                _ => return None,
+            }
+        }
+    }
+
+    if mapping.len() != a.states.len() || mapping.len() != b.states.len() {
+        return None;
+    }
+
+    Some(mapping)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_isomorphism;
+    use crate::fsm::{dfa, dfa_minimization, Dfa};
+    use crate::syntax_tree::parser;
+
+    fn minimized(regex: &str) -> Dfa {
+        let root = parser::parse(&regex.to_string()).unwrap();
+
+        dfa_minimization::minimize(&dfa::transform(root))
+    }
+
+    #[test]
+    fn reordered_alternatives_are_isomorphic() {
+        let a = minimized("a(b|c)");
+        let b = minimized("a(c|b)");
+
+        assert!(find_isomorphism(&a, &b).is_some());
+
+        let (a, b) = (a.canonical(), b.canonical());
+        assert_eq!(a.alphabet, b.alphabet);
+        assert_eq!(a.trans, b.trans);
+        assert_eq!(a.is_terminal, b.is_terminal);
+    }
+
+    #[test]
+    fn different_languages_are_not_isomorphic() {
+        assert!(find_isomorphism(&minimized("ab"), &minimized("a(b|c)")).is_none());
+        assert!(find_isomorphism(&minimized("a*"), &minimized("aa*")).is_none());
+    }
+
+    #[test]
+    fn unreachable_states_are_not_mapped() {
+        let a = minimized("a(b|c)");
+        let mut b = minimized("a(b|c)");
+        b.add_state(&vec![], false);
+
+        assert!(find_isomorphism(&a, &b).is_none());
+        assert!(find_isomorphism(&b, &a).is_none());
+        assert!(find_isomorphism(&a, &b.canonical()).is_some());
+    }
+}
@@ -1,6 +1,12 @@
 pub mod dfa;
 pub mod dfa_minimization;
 mod draw;
+pub mod isomorphism;
+pub mod nfa;
+pub mod nfa_parser;
+pub mod table;
+pub mod transducer;
+pub mod transducer_minimization;
 pub mod utils;
 
 use crate::syntax_tree::{GrammarType, Operations, SyntaxTree};
@@ -0,0 +1,91 @@
+use super::Dfa;
+
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug)]
+pub struct Nfa {
+    pub alphabet: Vec<String>,
+    pub states: Vec<String>,
+    pub trans: Vec<Vec<Vec<usize>>>,
+    pub epsilon_trans: Vec<Vec<usize>>,
+    pub start: usize,
+    pub is_terminal: HashSet<usize>,
+}
+
+impl Nfa {
+    // Subset construction: every DFA state is an epsilon-closed set of NFA states.
+    // State 0 of the result is the closure of the NFA start state
+    pub fn determinize(&self) -> Dfa {
+        let mut dfa = Dfa {
+            alphabet: self.alphabet.clone(),
+            states: vec![],
+            trans: vec![],
+            is_terminal: HashSet::new(),
+        };
+
+        let mut numbers: HashMap<Vec<usize>, usize> = HashMap::new();
+
+        let start_state = self.epsilon_closure(&[self.start]);
+        numbers.insert(start_state.clone(), 0);
+        dfa.add_state(&start_state, self.contains_terminal(&start_state));
+
+        let mut row = 0;
+
+        while row < dfa.states.len() {
+            for col in 0..dfa.alphabet.len() {
+                let new_state = self.move_by(&dfa.states[row], col);
+
+                if new_state.is_empty() {
+                    continue;
+                }
+
+                let new_row = match numbers.get(&new_state) {
+                    Some(v) => *v,
+                    _ => {
+                        dfa.add_state(&new_state, self.contains_terminal(&new_state));
+                        numbers.insert(new_state, dfa.states.len() - 1);
+                        dfa.states.len() - 1
+                    }
+                };
+
+                dfa.trans[row][col] = Some(new_row);
+            }
+
+            row += 1;
+        }
+
+        dfa
+    }
+
+    // Returns sorted set of states reachable from `states` by epsilon transitions
+    fn epsilon_closure(&self, states: &[usize]) -> Vec<usize> {
+        let mut closure: HashSet<usize> = states.iter().cloned().collect();
+        let mut stack: Vec<usize> = states.to_vec();
+
+        while let Some(v) = stack.pop() {
+            for to_state in self.epsilon_trans[v].iter() {
+                if closure.insert(*to_state) {
+                    stack.push(*to_state);
+                }
+            }
+        }
+
+        let mut closure: Vec<usize> = closure.into_iter().collect();
+        closure.sort_unstable();
+
+        closure
+    }
+
+    fn move_by(&self, states: &[usize], col: usize) -> Vec<usize> {
+        let to_states: Vec<usize> = states
+            .iter()
+            .flat_map(|v| self.trans[*v][col].iter().cloned())
+            .collect();
+
+        self.epsilon_closure(&to_states)
+    }
+
+    fn contains_terminal(&self, states: &[usize]) -> bool {
+        states.iter().any(|v| self.is_terminal.contains(v))
+    }
+}
@@ -0,0 +1,144 @@
+// Parses finite automaton from a text format:
+//
+// # Comments start with '#'
+// states: q0 q1 q2
+// alphabet: a b
+// start: q0
+// finals: q2
+// q0 a q0 q1
+// q1 & q2
+//
+// Every transition line is a source state, a symbol (`&` for epsilon)
+// and one or more target states
+use super::nfa::Nfa;
+
+use std::collections::{HashMap, HashSet};
+use std::error;
+use std::fs;
+
+const COMMENT_SYMBOL: char = '#';
+const EPSILON_SYMBOL: &str = "&";
+const SECTIONS: [&str; 4] = ["states", "alphabet", "start", "finals"];
+
+pub fn parse_from_file(filename: &str) -> Result<Nfa, Box<dyn error::Error>> {
+    let content = fs::read_to_string(filename)?;
+
+    parse(&content)
+}
+
+pub fn parse(content: &str) -> Result<Nfa, Box<dyn error::Error>> {
+    let mut sections: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut transitions: Vec<Vec<&str>> = vec![];
+
+    for line in content.lines() {
+        let line = line.split(COMMENT_SYMBOL).next().unwrap_or("").trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        // Only known keys start a section, states and symbols may contain ':'
+        let section = line
+            .find(':')
+            .map(|i| (line[..i].trim(), &line[i + 1..]))
+            .filter(|(key, _)| SECTIONS.contains(key));
+
+        match section {
+            Some((key, values)) => {
+                if sections.insert(key, values.split_whitespace().collect()).is_some() {
+                    return Err((String::from("Section ") + key + " is declared twice").into());
+                }
+            }
+            _ => transitions.push(line.split_whitespace().collect()),
+        }
+    }
+
+    let states = get_section(&sections, "states")?;
+    let alphabet = get_section(&sections, "alphabet")?;
+    let start = get_section(&sections, "start")?;
+    let finals = get_section(&sections, "finals")?;
+
+    if start.len() != 1 {
+        return Err("Exactly one start state must be provided".into());
+    }
+
+    let state_index: HashMap<&str, usize> = states.iter().enumerate().map(|(i, v)| (*v, i)).collect();
+    let symbol_index: HashMap<&str, usize> = alphabet.iter().enumerate().map(|(i, v)| (*v, i)).collect();
+
+    if state_index.len() != states.len() || symbol_index.len() != alphabet.len() {
+        return Err("States and alphabet symbols must be unique".into());
+    }
+
+    if symbol_index.contains_key(EPSILON_SYMBOL) {
+        return Err((String::from("Symbol ") + EPSILON_SYMBOL + " is reserved for epsilon").into());
+    }
+
+    let mut nfa = Nfa {
+        alphabet: alphabet.iter().map(|v| v.to_string()).collect(),
+        states: states.iter().map(|v| v.to_string()).collect(),
+        trans: vec![vec![vec![]; alphabet.len()]; states.len()],
+        epsilon_trans: vec![vec![]; states.len()],
+        start: find(&state_index, start[0], "state")?,
+        is_terminal: HashSet::new(),
+    };
+
+    for state in finals.iter() {
+        nfa.is_terminal.insert(find(&state_index, state, "state")?);
+    }
+
+    for transition in transitions.iter() {
+        if transition.len() < 3 {
+            return Err((String::from("Invalid transition ") + &transition.join(" ")).into());
+        }
+
+        let from_state = find(&state_index, transition[0], "state")?;
+
+        let targets = transition[2..]
+            .iter()
+            .map(|v| find(&state_index, v, "state"))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match transition[1] {
+            EPSILON_SYMBOL => nfa.epsilon_trans[from_state].extend(targets),
+            symbol => {
+                let col = find(&symbol_index, symbol, "symbol")?;
+                nfa.trans[from_state][col].extend(targets);
+            }
+        }
+    }
+
+    Ok(nfa)
+}
+
+fn get_section<'a>(sections: &HashMap<&str, Vec<&'a str>>, key: &str) -> Result<Vec<&'a str>, Box<dyn error::Error>> {
+    sections
+        .get(key)
+        .cloned()
+        .ok_or_else(|| (String::from("Section ") + key + " is not found").into())
+}
+
+fn find(index: &HashMap<&str, usize>, key: &str, kind: &str) -> Result<usize, Box<dyn error::Error>> {
+    index
+        .get(key)
+        .cloned()
+        .ok_or_else(|| (String::from("Unknown ") + kind + " " + key).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    #[test]
+    fn colon_in_transition_is_not_a_section() {
+        let nfa = parse("states: q:0 q1\nalphabet: a:b\nstart: q:0\nfinals: q1\nq:0 a:b q1\n").unwrap();
+
+        assert_eq!(nfa.states, vec!["q:0", "q1"]);
+        assert_eq!(nfa.alphabet, vec!["a:b"]);
+        assert_eq!(nfa.trans[0][0], vec![1]);
+    }
+
+    #[test]
+    fn missing_section_is_reported() {
+        assert!(parse("states: q0\nalphabet: a\nstart: q0\n").is_err());
+    }
+}
@@ -0,0 +1,170 @@
+use super::Dfa;
+
+use std::error;
+use std::str::FromStr;
+
+const START_MARKER: &str = "->";
+const TERMINAL_MARKER: &str = "*";
+const NO_TRANSITION: &str = "-";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TableFormat {
+    Text,
+    Markdown,
+    Csv,
+}
+
+impl FromStr for TableFormat {
+    type Err = Box<dyn error::Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(TableFormat::Text),
+            "markdown" | "md" => Ok(TableFormat::Markdown),
+            "csv" => Ok(TableFormat::Csv),
+            _ => Err((String::from("Unknown table format ") + s).into()),
+        }
+    }
+}
+
+impl Dfa {
+    // Renders transition table: rows are states, columns are alphabet symbols.
+    // State 0 is the start state, terminal states are marked with '*'
+    pub fn render_table(&self, format: TableFormat, with_sets: bool) -> String {
+        let rows = self.table_rows(format, with_sets);
+
+        match format {
+            TableFormat::Text => render_text(&rows),
+            TableFormat::Markdown => render_markdown(&rows),
+            TableFormat::Csv => render_csv(&rows),
+        }
+    }
+
+    fn table_rows(&self, format: TableFormat, with_sets: bool) -> Vec<Vec<String>> {
+        let mut rows = vec![];
+
+        let mut header = vec![String::from("State")];
+        if with_sets {
+            header.push(String::from("Set"));
+        }
+        header.extend(self.alphabet.iter().cloned());
+        rows.push(header);
+
+        for (state, to_states) in self.trans.iter().enumerate() {
+            let mut row = vec![self.state_label(state)];
+
+            if with_sets {
+                let set: Vec<String> = self.states[state].iter().map(|v| v.to_string()).collect();
+                row.push(format!("{{{}}}", set.join(",")));
+            }
+
+            to_states.iter().for_each(|to_state| match to_state {
+                Some(to_state) => row.push(to_state.to_string()),
+                None if format == TableFormat::Csv => row.push(String::new()),
+                None => row.push(NO_TRANSITION.to_string()),
+            });
+
+            rows.push(row);
+        }
+
+        rows
+    }
+
+    fn state_label(&self, state: usize) -> String {
+        let mut label = String::new();
+
+        if state == 0 {
+            label += START_MARKER;
+        }
+        if self.is_terminal.contains(&state) {
+            label += TERMINAL_MARKER;
+        }
+
+        label + &state.to_string()
+    }
+}
+
+fn render_text(rows: &[Vec<String>]) -> String {
+    let widths = column_widths(rows);
+
+    let mut result = String::new();
+
+    for (i, row) in rows.iter().enumerate() {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{:>width$}", cell, width = width))
+            .collect();
+        result += cells.join(" | ").trim_end();
+        result += "\n";
+
+        if i == 0 {
+            let separators: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+            result += &separators.join("-+-");
+            result += "\n";
+        }
+    }
+
+    result
+}
+
+fn render_markdown(rows: &[Vec<String>]) -> String {
+    let mut result = String::new();
+
+    for (i, row) in rows.iter().enumerate() {
+        let cells: Vec<String> = row.iter().map(|cell| escape_markdown(cell)).collect();
+        result += &format!("| {} |\n", cells.join(" | "));
+
+        if i == 0 {
+            let separators = vec!["---"; row.len()];
+            result += &format!("|{}|\n", separators.join("|"));
+        }
+    }
+
+    result
+}
+
+fn render_csv(rows: &[Vec<String>]) -> String {
+    let mut result = String::new();
+
+    for row in rows.iter() {
+        let cells: Vec<String> = row.iter().map(|cell| escape_csv(cell)).collect();
+        result += &cells.join(",");
+        result += "\n";
+    }
+
+    result
+}
+
+fn column_widths(rows: &[Vec<String>]) -> Vec<usize> {
+    let mut widths = vec![0; rows.first().map_or(0, |v| v.len())];
+
+    for row in rows.iter() {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    widths
+}
+
+fn escape_markdown(cell: &str) -> String {
+    let mut result = String::new();
+
+    for c in cell.chars() {
+        if "|\\*_`".contains(c) {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+
+    result
+}
+
+fn escape_csv(cell: &str) -> String {
+    if cell.contains([',', '"', '\n']) {
+        return format!("\"{}\"", cell.replace('"', "\"\""));
+    }
+
+    cell.to_string()
+}
@@ -0,0 +1,299 @@
+use super::{dfa, Dfa};
+use crate::syntax_tree::{parser, Operations};
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::error;
+use std::fs::File;
+
+const OUTPUT_DELIMITER: char = ':';
+// Every input:output pair is replaced with a private use area character,
+// so regex with output can be handled by the ordinary regex parser
+const PAIR_CODEPOINTS_START: u32 = 0xE000;
+const PAIR_CODEPOINTS_END: u32 = 0xF8FF;
+
+// Maps substituted character to input:output pair
+type Pairs = HashMap<String, (String, String)>;
+
+// Output is produced on transitions
+#[derive(Debug)]
+pub struct Mealy {
+    pub inputs: Vec<String>,
+    pub trans: Vec<Vec<Option<(usize, String)>>>,
+    pub is_terminal: HashSet<usize>,
+}
+
+// Output is produced on entering a state
+#[derive(Debug)]
+pub struct Moore {
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+    pub trans: Vec<Vec<Option<usize>>>,
+    pub is_terminal: HashSet<usize>,
+}
+
+// Builds Mealy machine out of regex with output, e.g. `a:x (b:y)*`.
+// Whitespace ends an output and is ignored otherwise, output of a symbol
+// may be empty (`a:`)
+pub fn transform(regex: &str) -> Result<Mealy, Box<dyn error::Error>> {
+    let (encoded_regex, pairs) = encode_pairs(regex)?;
+
+    let syntax_tree = parser::parse(&encoded_regex)?;
+
+    let dfa = dfa::transform(syntax_tree);
+
+    Mealy::from_dfa(&dfa, &pairs)
+}
+
+fn encode_pairs(regex: &str) -> Result<(String, Pairs), Box<dyn error::Error>> {
+    let mut encoded_regex = String::new();
+    let mut codepoints: HashMap<(String, String), String> = HashMap::new();
+
+    let mut chars = regex.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+
+        match Operations::from_char(&c) {
+            Some(Operations::OR) | Some(Operations::REPETITION) | Some(Operations::LBRACKET)
+            | Some(Operations::RBRACKET) => {
+                encoded_regex.push(c);
+                continue;
+            }
+            _ => (),
+        }
+
+        let input = match Operations::from_char(&c) {
+            Some(Operations::ESCAPE) => chars
+                .next()
+                .ok_or("Escape symbol must be followed by a character")?,
+            _ if c == OUTPUT_DELIMITER => return Err("Output must follow an input symbol".into()),
+            _ => c,
+        };
+
+        if chars.next() != Some(OUTPUT_DELIMITER) {
+            return Err((String::from("Symbol ") + &input.to_string() + " has no output").into());
+        }
+
+        let mut output = String::new();
+        while let Some(c) = chars.peek() {
+            match Operations::from_char(c) {
+                Some(Operations::ESCAPE) => {
+                    chars.next();
+                    output.push(chars.next().ok_or("Escape symbol must be followed by a character")?);
+                }
+                Some(Operations::OR) | Some(Operations::REPETITION) | Some(Operations::LBRACKET)
+                | Some(Operations::RBRACKET) => break,
+                _ if *c == OUTPUT_DELIMITER || c.is_whitespace() => break,
+                _ => {
+                    output.push(*c);
+                    chars.next();
+                }
+            }
+        }
+
+        let next_codepoint = PAIR_CODEPOINTS_START + codepoints.len() as u32;
+        if next_codepoint > PAIR_CODEPOINTS_END {
+            return Err("Too many different input:output pairs".into());
+        }
+
+        let codepoint = codepoints
+            .entry((input.to_string(), output))
+            .or_insert_with(|| std::char::from_u32(next_codepoint).unwrap().to_string());
+        encoded_regex += codepoint;
+    }
+
+    let pairs = codepoints.into_iter().map(|(pair, c)| (c, pair)).collect();
+
+    Ok((encoded_regex, pairs))
+}
+
+impl Mealy {
+    fn from_dfa(a: &Dfa, pairs: &Pairs) -> Result<Mealy, Box<dyn error::Error>> {
+        let mut inputs: Vec<String> = pairs.values().map(|(input, _)| input.clone()).collect();
+        inputs.sort();
+        inputs.dedup();
+
+        let mut mealy = Mealy {
+            trans: vec![vec![None; inputs.len()]; a.states.len()],
+            inputs,
+            is_terminal: a.is_terminal.clone(),
+        };
+
+        for (state, to_states) in a.trans.iter().enumerate() {
+            for (c, to_state) in to_states.iter().enumerate() {
+                let to_state = match to_state {
+                    Some(v) => *v,
+                    _ => continue,
+                };
+
+                let (input, output) = &pairs[&a.alphabet[c]];
+                let i = mealy.input_index(input).unwrap();
+
+                if let Some((prev_state, prev_output)) = &mealy.trans[state][i] {
+                    if *prev_state != to_state || prev_output != output {
+                        return Err((String::from("Transducer is not deterministic on input ")
+                            + input
+                            + " (outputs "
+                            + prev_output
+                            + " and "
+                            + output
+                            + ")")
+                            .into());
+                    }
+                }
+
+                mealy.trans[state][i] = Some((to_state, output.clone()));
+            }
+        }
+
+        Ok(mealy)
+    }
+
+    pub fn input_index(&self, input: &str) -> Option<usize> {
+        self.inputs.iter().position(|v| v == input)
+    }
+
+    // Returns produced output if the whole input is accepted
+    pub fn translate(&self, input: &[String]) -> Option<String> {
+        let mut state = 0;
+        let mut output = String::new();
+
+        for symbol in input.iter() {
+            let (to_state, symbol_output) = self.trans.get(state)?[self.input_index(symbol)?].as_ref()?;
+            output += symbol_output;
+            state = *to_state;
+        }
+
+        match self.is_terminal.contains(&state) {
+            true => Some(output),
+            _ => None,
+        }
+    }
+
+    // Every state of Moore machine is a pair of Mealy state and output
+    // produced while entering it. Start state has empty output
+    pub fn to_moore(&self) -> Moore {
+        let mut moore = Moore {
+            inputs: self.inputs.clone(),
+            outputs: vec![],
+            trans: vec![],
+            is_terminal: HashSet::new(),
+        };
+
+        let mut states: HashMap<(usize, String), usize> = HashMap::new();
+        let mut queue = VecDeque::<(usize, String)>::new();
+
+        states.insert((0, String::new()), 0);
+        moore.add_state(String::new(), self.is_terminal.contains(&0));
+        queue.push_back((0, String::new()));
+
+        while let Some(state) = queue.pop_front() {
+            let row = states[&state];
+
+            for (c, to_state) in self.trans[state.0].iter().enumerate() {
+                let to_state = match to_state {
+                    Some(v) => v.clone(),
+                    _ => continue,
+                };
+
+                let to_row = match states.get(&to_state) {
+                    Some(v) => *v,
+                    _ => {
+                        moore.add_state(to_state.1.clone(), self.is_terminal.contains(&to_state.0));
+                        states.insert(to_state.clone(), moore.outputs.len() - 1);
+                        queue.push_back(to_state);
+                        moore.outputs.len() - 1
+                    }
+                };
+
+                moore.trans[row][c] = Some(to_row);
+            }
+        }
+
+        moore
+    }
+
+    pub fn render_to(&self, output: &str) {
+        let mut f = File::create(output).unwrap();
+        dot::render(self, &mut f).unwrap()
+    }
+}
+
+impl Moore {
+    fn add_state(&mut self, output: String, is_terminal: bool) {
+        self.outputs.push(output);
+        self.trans.push(vec![None; self.inputs.len()]);
+
+        if is_terminal {
+            self.is_terminal.insert(self.outputs.len() - 1);
+        }
+    }
+
+    pub fn input_index(&self, input: &str) -> Option<usize> {
+        self.inputs.iter().position(|v| v == input)
+    }
+
+    // Returns produced output if the whole input is accepted.
+    // Output of the start state is produced before reading any input
+    pub fn translate(&self, input: &[String]) -> Option<String> {
+        let mut state = 0;
+        let mut output = self.outputs.get(state)?.clone();
+
+        for symbol in input.iter() {
+            state = self.trans[state][self.input_index(symbol)?]?;
+            output += &self.outputs[state];
+        }
+
+        match self.is_terminal.contains(&state) {
+            true => Some(output),
+            _ => None,
+        }
+    }
+
+    // Output of the target state is moved onto transition.
+    // Output of the start state can't be represented and is lost
+    pub fn to_mealy(&self) -> Mealy {
+        let trans = self
+            .trans
+            .iter()
+            .map(|to_states| {
+                to_states
+                    .iter()
+                    .map(|to_state| to_state.map(|v| (v, self.outputs[v].clone())))
+                    .collect()
+            })
+            .collect();
+
+        Mealy {
+            inputs: self.inputs.clone(),
+            trans,
+            is_terminal: self.is_terminal.clone(),
+        }
+    }
+
+    pub fn render_to(&self, output: &str) {
+        let mut f = File::create(output).unwrap();
+        dot::render(self, &mut f).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::transform;
+
+    fn translate(regex: &str, input: &str) -> Option<String> {
+        let input: Vec<String> = input.chars().map(|c| c.to_string()).collect();
+
+        transform(regex).unwrap().translate(&input)
+    }
+
+    #[test]
+    fn whitespace_ends_output() {
+        assert_eq!(translate("a:x b:y", "ab"), Some(String::from("xy")));
+        assert_eq!(translate("a:xy (b:z)*", "abb"), Some(String::from("xyzz")));
+        assert_eq!(translate("a:x\\ y b:", "ab"), Some(String::from("x y")));
+        assert_eq!(translate("a:x b:y", "a"), None);
+    }
+}
@@ -0,0 +1,171 @@
+use super::transducer::{Mealy, Moore};
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+pub fn minimize_mealy(a: &Mealy) -> Mealy {
+    let reachable = find_reachable(&a.trans, |v| v.as_ref().map(|(state, _)| *state));
+
+    // States are initially split by acceptance and outputs on every input
+    let initial: Vec<(bool, Vec<Option<String>>)> = reachable
+        .iter()
+        .map(|state| {
+            let outputs = a.trans[*state]
+                .iter()
+                .map(|v| v.as_ref().map(|(_, output)| output.clone()))
+                .collect();
+            (a.is_terminal.contains(state), outputs)
+        })
+        .collect();
+
+    let components = refine(&reachable, initial, |state, c| {
+        a.trans[state][c].as_ref().map(|(to_state, _)| *to_state)
+    }, a.inputs.len());
+
+    #[cfg(debug_assertions)]
+    println!("Mealy components : {:?}", components);
+
+    let mut new_mealy = Mealy {
+        inputs: a.inputs.clone(),
+        trans: vec![],
+        is_terminal: HashSet::new(),
+    };
+
+    for state in reachable.iter() {
+        let component = components[state];
+        if component < new_mealy.trans.len() {
+            continue;
+        }
+
+        new_mealy.trans.push(
+            a.trans[*state]
+                .iter()
+                .map(|v| v.as_ref().map(|(to_state, output)| (components[to_state], output.clone())))
+                .collect(),
+        );
+
+        if a.is_terminal.contains(state) {
+            new_mealy.is_terminal.insert(component);
+        }
+    }
+
+    new_mealy
+}
+
+pub fn minimize_moore(a: &Moore) -> Moore {
+    let reachable = find_reachable(&a.trans, |v| *v);
+
+    // States are initially split by acceptance and output
+    let initial: Vec<(bool, String)> = reachable
+        .iter()
+        .map(|state| (a.is_terminal.contains(state), a.outputs[*state].clone()))
+        .collect();
+
+    let components = refine(&reachable, initial, |state, c| a.trans[state][c], a.inputs.len());
+
+    #[cfg(debug_assertions)]
+    println!("Moore components : {:?}", components);
+
+    let mut new_moore = Moore {
+        inputs: a.inputs.clone(),
+        outputs: vec![],
+        trans: vec![],
+        is_terminal: HashSet::new(),
+    };
+
+    for state in reachable.iter() {
+        let component = components[state];
+        if component < new_moore.trans.len() {
+            continue;
+        }
+
+        new_moore.outputs.push(a.outputs[*state].clone());
+        new_moore.trans.push(a.trans[*state].iter().map(|v| v.map(|to_state| components[&to_state])).collect());
+
+        if a.is_terminal.contains(state) {
+            new_moore.is_terminal.insert(component);
+        }
+    }
+
+    new_moore
+}
+
+// Returns states reachable from the start state in BFS order
+fn find_reachable<T, F>(trans: &[Vec<T>], target: F) -> Vec<usize>
+where
+    F: Fn(&T) -> Option<usize>,
+{
+    let mut reachable = vec![];
+
+    if trans.is_empty() {
+        return reachable;
+    }
+
+    let mut visited = HashSet::<usize>::new();
+    let mut queue = VecDeque::<usize>::new();
+
+    visited.insert(0);
+    queue.push_back(0);
+
+    while let Some(v) = queue.pop_front() {
+        reachable.push(v);
+
+        for state in trans[v].iter().filter_map(&target) {
+            if visited.insert(state) {
+                queue.push_back(state);
+            }
+        }
+    }
+
+    reachable
+}
+
+// Splits states into equivalence classes until every class is stable.
+// Classes are numbered in order of the first occurrence, so start state gets 0
+fn refine<K, F>(states: &[usize], initial: Vec<K>, target: F, inputs_len: usize) -> HashMap<usize, usize>
+where
+    K: Eq + Hash,
+    F: Fn(usize, usize) -> Option<usize>,
+{
+    let mut classes = numerate(initial);
+    let mut classes_count = classes.iter().max().map_or(0, |v| v + 1);
+
+    loop {
+        let class_of: HashMap<usize, usize> = states.iter().cloned().zip(classes.iter().cloned()).collect();
+
+        let signatures: Vec<(usize, Vec<Option<usize>>)> = states
+            .iter()
+            .zip(classes.iter())
+            .map(|(state, class)| {
+                let targets = (0..inputs_len)
+                    .map(|c| target(*state, c).map(|to_state| class_of[&to_state]))
+                    .collect();
+                (*class, targets)
+            })
+            .collect();
+
+        let new_classes = numerate(signatures);
+        let new_classes_count = new_classes.iter().max().map_or(0, |v| v + 1);
+
+        classes = new_classes;
+
+        if new_classes_count == classes_count {
+            break;
+        }
+
+        classes_count = new_classes_count;
+    }
+
+    states.iter().cloned().zip(classes).collect()
+}
+
+fn numerate<K: Eq + Hash>(keys: Vec<K>) -> Vec<usize> {
+    let mut numbers: HashMap<K, usize> = HashMap::new();
+
+    keys.into_iter()
+        .map(|key| {
+            let next_number = numbers.len();
+            *numbers.entry(key).or_insert(next_number)
+        })
+        .collect()
+}
@@ -0,0 +1,2 @@
+pub mod fsm;
+pub mod syntax_tree;
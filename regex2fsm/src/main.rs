@@ -1,6 +1,6 @@
-mod fsm;
-mod syntax_tree;
+use regex2fsm::{fsm, syntax_tree};
 
+use fsm::table::TableFormat;
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -10,24 +10,141 @@ use structopt::StructOpt;
 )]
 struct Opt {
     ///
-    #[structopt()]
-    regex: String,
+    #[structopt(required_unless = "automaton")]
+    regex: Option<String>,
+
+    /// File with finite automaton (states, alphabet, start, finals and transitions)
+    /// which is determinized instead of regex
+    #[structopt(short, long, conflicts_with_all = &["regex", "transducer"])]
+    automaton: Option<String>,
+
+    /// Print transition tables instead of debug output (text, markdown or csv)
+    #[structopt(short, long, possible_values = &["text", "markdown", "md", "csv"])]
+    table: Option<TableFormat>,
+
+    /// Show the set behind every state in transition tables
+    #[structopt(short, long)]
+    sets: bool,
+
+    /// Regex whose minimized automaton is compared with the minimized automaton of <regex>
+    #[structopt(short, long, conflicts_with = "transducer")]
+    compare: Option<String>,
+
+    /// Treat regex as regex with output (e.g. `a:x (b:y)*`) and build transducers
+    #[structopt(long)]
+    transducer: bool,
+
+    /// Input word to translate with the built transducers
+    #[structopt(short, long, requires = "transducer")]
+    input: Option<String>,
 }
 
 fn main() {
     let opt = Opt::from_args();
 
-    let result = syntax_tree::parser::parse(&opt.regex);
+    if opt.transducer {
+        run_transducer(&opt);
+        return;
+    }
+
+    let dfa = match &opt.automaton {
+        Some(filename) => {
+            let nfa = match fsm::nfa_parser::parse_from_file(filename) {
+                Ok(v) => v,
+                Err(e) => {
+                    panic!("Failed to parse automaton from file: {}", e);
+                }
+            };
+
+            println!("Nfa: {:#?}", nfa);
 
-    let dfa = fsm::dfa::transform(result.unwrap());
+            nfa.determinize()
+        }
+        _ => {
+            let result = syntax_tree::parser::parse(opt.regex.as_ref().unwrap());
 
-    println!("Dfa: {:#?}", dfa);
+            fsm::dfa::transform(result.unwrap())
+        }
+    };
+
+    match opt.table {
+        Some(format) => println!("Dfa:\n{}", dfa.render_table(format, opt.sets)),
+        _ => println!("Dfa: {:#?}", dfa),
+    }
 
     dfa.render_to("dfa.dot");
 
     let minimized_dfa = fsm::dfa_minimization::minimize(&dfa);
 
-    println!("Minimized dfa: {:#?}", minimized_dfa);
+    match opt.table {
+        Some(format) => println!("Minimized dfa:\n{}", minimized_dfa.render_table(format, opt.sets)),
+        _ => println!("Minimized dfa: {:#?}", minimized_dfa),
+    }
 
     minimized_dfa.render_to("min_dfa.dot");
+
+    if let Some(other_regex) = &opt.compare {
+        compare(&minimized_dfa, other_regex, opt.table, opt.sets);
+    }
+}
+
+fn compare(minimized_dfa: &fsm::Dfa, other_regex: &String, table: Option<TableFormat>, sets: bool) {
+    let result = syntax_tree::parser::parse(other_regex);
+
+    let other_dfa = fsm::dfa::transform(result.unwrap());
+    let other_minimized_dfa = fsm::dfa_minimization::minimize(&other_dfa);
+
+    match fsm::isomorphism::find_isomorphism(minimized_dfa, &other_minimized_dfa) {
+        Some(mapping) => {
+            let mut mapping: Vec<(usize, usize)> = mapping.into_iter().collect();
+            mapping.sort();
+            println!("Minimized automata are isomorphic, state mapping: {:?}", mapping);
+        }
+        _ => println!("Minimized automata are not isomorphic"),
+    }
+
+    let canonical = minimized_dfa.canonical();
+
+    match table {
+        Some(format) => println!("Canonical form:\n{}", canonical.render_table(format, sets)),
+        _ => println!("Canonical form: {:#?}", canonical),
+    }
+}
+
+fn run_transducer(opt: &Opt) {
+    let mealy = match fsm::transducer::transform(opt.regex.as_ref().unwrap()) {
+        Ok(v) => v,
+        Err(e) => {
+            panic!("Failed to build transducer: {}", e);
+        }
+    };
+
+    println!("Mealy: {:#?}", mealy);
+
+    mealy.render_to("mealy.dot");
+
+    let minimized_mealy = fsm::transducer_minimization::minimize_mealy(&mealy);
+
+    println!("Minimized mealy: {:#?}", minimized_mealy);
+
+    minimized_mealy.render_to("min_mealy.dot");
+
+    let moore = fsm::transducer_minimization::minimize_moore(&minimized_mealy.to_moore());
+
+    println!("Minimized moore: {:#?}", moore);
+
+    moore.render_to("moore.dot");
+
+    if let Some(input) = &opt.input {
+        let input: Vec<String> = input.chars().map(|c| c.to_string()).collect();
+
+        match minimized_mealy.translate(&input) {
+            Some(output) => println!("Mealy output: {}", output),
+            _ => println!("Input is rejected"),
+        }
+
+        if let Some(output) = moore.translate(&input) {
+            println!("Moore output: {}", output);
+        }
+    }
 }
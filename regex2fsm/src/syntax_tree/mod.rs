@@ -37,7 +37,7 @@ impl SyntaxTree {
 }
 
 impl Operations {
-    fn from_char(c: &char) -> Option<Operations> {
+    pub fn from_char(c: &char) -> Option<Operations> {
         match c {
             '|' => Some(Operations::OR),
             '*' => Some(Operations::REPETITION),